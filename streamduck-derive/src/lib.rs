@@ -0,0 +1,94 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+#![warn(missing_docs)]
+//! Derive macros for streamduck-rust-client
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, DeriveInput, Error, LitStr, Type};
+
+/// Implements `StreamduckRequest` for the type
+///
+/// ```ignore
+/// #[derive(Serialize, StreamduckRequest)]
+/// #[request(plugin = "Core", name = "List Devices", response = Vec<Device>, idempotent)]
+/// pub struct ListDevices;
+/// ```
+///
+/// `response` defaults to `serde::de::IgnoredAny` for requests that don't respond with anything useful,
+/// `idempotent` marks requests that are safe to send again after reconnecting
+#[proc_macro_derive(StreamduckRequest, attributes(request))]
+pub fn derive_streamduck_request(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.to_compile_error().into()
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut plugin: Option<LitStr> = None;
+    let mut name: Option<LitStr> = None;
+    let mut response: Option<Type> = None;
+    let mut idempotent = false;
+
+    for attribute in input.attrs.iter().filter(|attr| attr.path().is_ident("request")) {
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("plugin") {
+                plugin = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("response") {
+                response = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("idempotent") {
+                idempotent = true;
+            } else {
+                return Err(meta.error("Unknown request attribute, expected plugin, name, response or idempotent"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    let Some(plugin) = plugin else {
+        return Err(Error::new(Span::call_site(), "Missing #[request(plugin = \"...\")] attribute"));
+    };
+
+    let Some(name) = name else {
+        return Err(Error::new(Span::call_site(), "Missing #[request(name = \"...\")] attribute"));
+    };
+
+    let response = response
+        .map(|response| quote!(#response))
+        .unwrap_or_else(|| quote!(::streamduck_rust_client::__serde::de::IgnoredAny));
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    let idempotent = if idempotent {
+        quote! {
+            fn idempotent(&self) -> bool {
+                true
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::streamduck_rust_client::api::StreamduckRequest for #ident #type_generics #where_clause {
+            type Response = #response;
+
+            fn name(&self) -> ::streamduck_rust_client::base::NamespacedName {
+                ::streamduck_rust_client::base::NamespacedName::new(#plugin, #name)
+            }
+
+            #idempotent
+        }
+    })
+}
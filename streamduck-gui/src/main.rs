@@ -5,119 +5,584 @@
  */
 
 mod ui;
+mod templates;
+mod profiles;
 
-use std::sync::{Arc, Condvar};
-use streamduck_rust_client::event::StreamduckEvent;
+use std::collections::{BTreeSet, HashMap};
+use std::env;
+use std::fmt::Display;
+use std::future::pending;
+use std::time::Duration;
+use streamduck_rust_client::event::{SocketEvent, StreamduckEvent};
 use streamduck_rust_client::Streamduck;
 use tokio::sync::mpsc;
-use streamduck_rust_client::api::{Device, Input, PartialScreenItem};
+use tokio::task::JoinHandle;
+use tokio::time::{sleep_until, Instant};
+use streamduck_rust_client::api::{Device, Input, ItemProperties, PartialScreenItem};
 use streamduck_rust_client::base::NamespacedDeviceIdentifier;
-use crate::ui::{ui_main, UIMessage};
+use streamduck_rust_client::connection::ConnectionState;
+use streamduck_rust_client::device::DeviceHandle;
+use streamduck_rust_client::registry::DeviceRegistry;
+use streamduck_rust_client::version::ConnectionInfo;
+use crate::ui::{ui_main, UIMessage, UIRequest};
+use crate::ui::operations::OperationId;
+use crate::ui::notifications::Notification;
 
 #[tokio::main]
 async fn main() {
-    let streamduck = Arc::new(Streamduck::new(None).await.unwrap());
-
     let (api_tx, api_rx) = mpsc::channel::<APIMessage>(50);
-    let (ui_tx, mut ui_rx) = mpsc::channel::<UIMessage>(50);
+    let (ui_tx, mut ui_rx) = mpsc::channel::<UIRequest>(50);
 
     let (waker, waiter) = mpsc::channel::<()>(1);
-    let waker_copy = waker.clone();
-
-    let (streamduck_copy, api_tx_copy) = (streamduck.clone(), api_tx.clone());
-    let receive_events = async move {
-        while let Some(event) = streamduck.wait_for_event().await {
-            if match event {
-                StreamduckEvent::DeviceConnected(device) => {
-                    api_tx.send(APIMessage::ConnectedDevice(device)).await.ok();
-                    true
-                }
-                StreamduckEvent::DeviceDisconnected(device) => {
-                    api_tx.send(APIMessage::DisconnectedDevice(device)).await.ok();
-                    true
-                }
-                StreamduckEvent::DeviceAppeared(device) => {
-                    api_tx.send(APIMessage::NewDevice(device)).await.ok();
-                    true
-                }
-                StreamduckEvent::DeviceDisappeared(device) => {
-                    api_tx.send(APIMessage::DeviceGone(device)).await.ok();
-                    true
-                }
-                StreamduckEvent::Other(_) => false,
-                StreamduckEvent::ClientConnected => {
-                    println!("Connected!");
-                    false
+    let api_tx = APISender {
+        messages: api_tx,
+        waker
+    };
+
+    let receive_ui_messages = async move {
+        // UI picks the URL to connect to, since it knows which one was used last time
+        let mut connection: Option<Connection> = None;
+
+        while let Some(UIRequest { id, message }) = ui_rx.recv().await {
+            let message = match (message, &connection) {
+                // Closed client won't connect again, so it's replaced with a new one
+                (UIMessage::RetryConnection, Some(current)) if current.streamduck.connection_state().is_closed() => {
+                    UIMessage::SwitchConnection(current.streamduck.url().to_string())
                 }
-                StreamduckEvent::ClientDisconnected => {
-                    println!("Disconnected!");
-                    false
+                (message, _) => message
+            };
+
+            // Connection messages are handled here, so a request stuck on an unresponsive daemon can't hold them up
+            let result = match message {
+                UIMessage::SwitchConnection(url) => {
+                    // Old client has to be gone before the new one starts reporting
+                    drop(connection.take());
+                    connection = Connection::start(&url, &api_tx).await;
+
+                    // Failure was already reported when starting
+                    api_tx.send(APIMessage::Completed { id, success: connection.is_some() }).await;
+                    continue;
                 }
-                StreamduckEvent::SocketError(error) => {
-                    println!("Error from socket! {}", error);
-                    false
+
+                UIMessage::RetryConnection => match &connection {
+                    Some(connection) if connection.streamduck.retry() => Ok(()),
+                    Some(_) => Err(failure("Nothing to retry")("Already connected to daemon")),
+                    None => Err(not_connected())
+                },
+
+                message => match &connection {
+                    Some(connection) => match connection.requests.send(UIRequest { id, message }) {
+                        // Completion is reported by the connection's request handler
+                        Ok(()) => continue,
+                        Err(_) => Err(failure("Failed to send request")("Request handler has stopped"))
+                    },
+                    None => Err(not_connected())
                 }
-            } {
-                waker.send(()).await.ok();
-            }
+            };
+
+            complete(&api_tx, id, result).await;
         }
     };
 
-    let receive_ui_messages = async move {
-        let devices = streamduck_copy.list_devices().await.unwrap();
-        api_tx_copy.send(APIMessage::DeviceList(devices)).await.ok();
-        waker_copy.send(()).await.ok();
-
-        while let Some(message) = ui_rx.recv().await {
-            match message {
-                UIMessage::SetDeviceAutoconnect { identifier, autoconnect } => {
-                    streamduck_copy.set_device_autoconnect(identifier, autoconnect).await.ok();
-                }
-                UIMessage::ConnectDevice(identifier) => {
-                    streamduck_copy.connect_device(identifier).await.ok();
+    tokio::spawn(receive_ui_messages);
+
+    ui_main(ui_tx, api_rx, waiter, url_override())
+}
+
+/// Daemon URL from `--url` argument or `STREAMDUCK_URL` environment variable
+fn url_override() -> Option<String> {
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        if arg == "--url" {
+            if let Some(url) = args.next() {
+                return Some(url);
+            }
+        } else if let Some(url) = arg.strip_prefix("--url=") {
+            return Some(url.to_string());
+        }
+    }
+
+    env::var("STREAMDUCK_URL").ok()
+        .filter(|url| !url.trim().is_empty())
+}
+
+/// Client with the tasks that forward its state to the UI, both are torn down on drop
+struct Connection {
+    streamduck: Streamduck,
+    /// Requests are handled one at a time in their own task, in order they were sent
+    requests: mpsc::UnboundedSender<UIRequest>,
+    tasks: Vec<JoinHandle<()>>
+}
+
+/// State that requests of a connection are handled with
+struct RequestHandler {
+    streamduck: Streamduck,
+    registry: DeviceRegistry,
+    /// Kept around so the input layout is only retrieved once per device
+    devices: HashMap<NamespacedDeviceIdentifier, DeviceHandle>
+}
+
+impl RequestHandler {
+    /// Handle of the device, created the first time it's needed
+    fn device(&mut self, identifier: NamespacedDeviceIdentifier) -> DeviceHandle {
+        self.devices.entry(identifier.clone())
+            .or_insert_with(|| self.streamduck.device(identifier))
+            .clone()
+    }
+}
+
+impl Connection {
+    /// Creates client for the URL, reports the URL as closed connection if it's invalid
+    async fn start(url: &str, api_tx: &APISender) -> Option<Connection> {
+        let streamduck = match Streamduck::new(Some(url)).await {
+            Ok(streamduck) => streamduck,
+            Err(error) => {
+                api_tx.error("Failed to connect to daemon", &error).await;
+                api_tx.send(APIMessage::ConnectionState {
+                    url: url.to_string(),
+                    state: ConnectionState::Closed {
+                        reason: Some(error.to_string())
+                    }
+                }).await;
+
+                return None;
+            }
+        };
+
+        let registry = DeviceRegistry::new(&streamduck);
+        let api_tx_devices = api_tx.clone();
+        let mut devices = registry.watch();
+        let receive_devices = async move {
+
+            while devices.changed().await.is_ok() {
+                let list = devices.borrow_and_update().clone();
+                api_tx_devices.send(APIMessage::DeviceList(list)).await;
+            }
+        };
+
+        let (api_tx_connection, url) = (api_tx.clone(), streamduck.url().to_string());
+        let mut connection = streamduck.watch_connection_state();
+        let receive_connection_state = async move {
+            loop {
+                let state = connection.borrow_and_update().clone();
+                api_tx_connection.send(APIMessage::ConnectionState { url: url.clone(), state }).await;
+
+                if connection.changed().await.is_err() {
+                    break;
                 }
-                UIMessage::GetDeviceState(identifier) => {
-                    match streamduck_copy.get_device_inputs(identifier.clone()).await {
-                        Ok(grid) => {
-                            api_tx_copy.send(APIMessage::InputGrid(grid)).await.ok();
-                        }
-                        Err(error) => {
-                            println!("Error while trying to get inputs! {error}")
+            }
+        };
+
+        let (requests, mut request_rx) = mpsc::unbounded_channel::<UIRequest>();
+        let mut handler = RequestHandler {
+            streamduck: streamduck.clone(),
+            registry,
+            devices: HashMap::new()
+        };
+        let api_tx_requests = api_tx.clone();
+        let receive_requests = async move {
+            while let Some(UIRequest { id, message }) = request_rx.recv().await {
+                let result = handle_ui_message(&mut handler, &api_tx_requests, message).await;
+                complete(&api_tx_requests, id, result).await;
+            }
+        };
+
+        // Not torn down with the other tasks, requests that are left fail against the closed client and still get completed
+        tokio::spawn(receive_requests);
+
+        let api_tx = api_tx.clone();
+        let mut events = streamduck.subscribe();
+        let receive_events = async move {
+            let mut item_changes = ItemChanges::default();
+
+            loop {
+                let event = tokio::select! {
+                    event = events.recv() => event,
+                    _ = item_changes.elapsed() => {
+                        for (identifier, indices) in item_changes.take() {
+                            api_tx.send(APIMessage::ItemChanged { identifier, indices }).await;
                         }
+                        continue;
                     }
+                };
 
-                    match streamduck_copy.get_device_screen_stack(identifier).await {
-                        Ok(stack) => {
-                            api_tx_copy.send(APIMessage::Stack(stack)).await.ok();
-                        }
-                        Err(error) => {
-                            println!("Error while trying to get screen stack! {error}")
+                let Some(event) = event else {
+                    break;
+                };
+
+                // Device list is kept up to date by the registry, these are only reported
+                match event {
+                    StreamduckEvent::DeviceConnected(device) => {
+                        api_tx.notify(Notification::info("Device connected").with_details(device.identifier)).await;
+                    }
+                    StreamduckEvent::DeviceDisconnected(identifier) => {
+                        api_tx.notify(Notification::info("Device disconnected").with_details(identifier)).await;
+                    }
+                    StreamduckEvent::DeviceAppeared(device) => {
+                        api_tx.notify(Notification::info("Device appeared").with_details(device.identifier)).await;
+                    }
+                    StreamduckEvent::DeviceDisappeared(identifier) => {
+                        api_tx.notify(Notification::info("Device disappeared").with_details(identifier)).await;
+                    }
+                    StreamduckEvent::Other(event) => {
+                        if let Some((identifier, index)) = item_change(&event) {
+                            item_changes.add(identifier, index);
                         }
                     }
+                    StreamduckEvent::ClientConnected => {
+                        api_tx.notify(Notification::info("Connected to daemon")).await;
+                    }
+                    StreamduckEvent::ClientDisconnected => {
+                        api_tx.notify(Notification::error("Lost connection to daemon")).await;
+                    }
+                    StreamduckEvent::SocketError(error) => {
+                        api_tx.error("Daemon reported an error", error).await;
+                    }
+                    StreamduckEvent::VersionChecked(info) => {
+                        api_tx.send(APIMessage::ConnectionInfo(info)).await;
+                    }
+                    StreamduckEvent::Lagged(count) => {
+                        api_tx.error("Missed events from daemon", format!("{count} events were skipped, shown state might be outdated")).await;
+                    }
                 }
-                UIMessage::PopScreen(identifier) => {
-                    streamduck_copy.pop_screen(identifier).await.ok();
+            }
+        };
+
+        Some(Connection {
+            streamduck,
+            requests,
+            tasks: vec![
+                tokio::spawn(receive_events),
+                tokio::spawn(receive_devices),
+                tokio::spawn(receive_connection_state)
+            ]
+        })
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.streamduck.close();
+
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+/// Operation that didn't succeed, reported to the user as notification
+struct Failure {
+    title: &'static str,
+    details: String
+}
+
+/// Turns an error into [Failure] with the title
+fn failure<E: Display>(title: &'static str) -> impl FnOnce(E) -> Failure {
+    move |error| Failure {
+        title,
+        details: error.to_string()
+    }
+}
+
+fn not_connected() -> Failure {
+    failure("Not connected to daemon")("Daemon URL is invalid, pick another one in connection settings")
+}
+
+/// Reports failure if there's one, then tells the UI that the operation has finished
+async fn complete(api_tx: &APISender, id: OperationId, result: Result<(), Failure>) {
+    if let Err(failure) = &result {
+        api_tx.error(failure.title, &failure.details).await;
+    }
+
+    api_tx.send(APIMessage::Completed { id, success: result.is_ok() }).await;
+}
+
+async fn handle_ui_message(handler: &mut RequestHandler, api_tx: &APISender, message: UIMessage) -> Result<(), Failure> {
+    match message {
+        UIMessage::SetDeviceAutoconnect { identifier, autoconnect } => {
+            handler.streamduck.set_device_autoconnect(identifier.clone(), autoconnect).await
+                .map_err(failure("Failed to change autoconnect"))?;
+
+            // Daemon doesn't report the change, registry would keep the old value otherwise
+            handler.registry.set_autoconnect(&identifier, autoconnect);
+        }
+        UIMessage::ConnectDevice(identifier) => {
+            handler.streamduck.connect_device(identifier).await
+                .map_err(failure("Failed to connect to device"))?;
+        }
+        UIMessage::GetDeviceState(identifier) => {
+            let device = handler.device(identifier);
+
+            let grid = device.inputs().await
+                .map_err(failure("Failed to get device's inputs"))?;
+            api_tx.send(APIMessage::InputGrid { identifier: device.identifier().clone(), grid }).await;
+
+            refresh_screen(&device, api_tx).await?;
+        }
+        // Handled by the caller, as they have to get through while a request is stuck
+        UIMessage::RetryConnection | UIMessage::SwitchConnection(_) => {}
+        UIMessage::RefreshScreen(identifier) => {
+            refresh_screen(&handler.device(identifier), api_tx).await?;
+        }
+        UIMessage::GetItemProperties { identifier, input } => {
+            let properties = handler.streamduck.get_item_properties(identifier.clone(), input).await
+                .map_err(failure("Failed to get item properties"))?;
+            api_tx.send(APIMessage::ItemProperties { identifier, input, properties }).await;
+        }
+        UIMessage::SetItemProperties { identifier, input, properties } => {
+            let device = handler.device(identifier);
+
+            device.set_item_properties(input, properties).await
+                .map_err(failure("Failed to save item properties"))?;
+            refresh_screen(&device, api_tx).await?;
+        }
+        UIMessage::PopScreen(identifier) => {
+            let device = handler.device(identifier);
+
+            let popped = device.pop_screen().await
+                .map_err(failure("Failed to pop screen"))?;
+            if !popped {
+                return Err(failure("Failed to pop screen")("Root screen can't be popped"));
+            }
+
+            refresh_screen(&device, api_tx).await?;
+        }
+        UIMessage::PopToDepth { identifier, depth } => {
+            let device = handler.device(identifier);
+
+            // Stack could've changed since it was shown, so popping by what daemon says
+            loop {
+                let stack = device.screen_stack().await
+                    .map_err(failure("Failed to get screen stack"))?;
+
+                if stack.len() <= depth + 1 {
+                    break;
+                }
+
+                let popped = device.pop_screen().await
+                    .map_err(failure("Failed to pop screen"))?;
+                if !popped {
+                    break;
                 }
             }
+
+            refresh_screen(&device, api_tx).await?;
         }
-    };
+        UIMessage::PushScreen(identifier) => {
+            let device = handler.device(identifier);
 
-    tokio::spawn(receive_events);
-    tokio::spawn(receive_ui_messages);
+            device.push_new_empty_screen().await
+                .map_err(failure("Failed to push screen"))?;
+            refresh_screen(&device, api_tx).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Events that plugins send when screen items change, with device's identifier and optionally an input index
+const ITEM_CHANGE_EVENTS: [&str; 2] = ["Item Changed", "Screen Changed"];
+
+/// How long item changes are collected before the UI is told about them
+const ITEM_CHANGE_DELAY: Duration = Duration::from_millis(100);
+
+fn item_change(event: &SocketEvent) -> Option<(NamespacedDeviceIdentifier, Option<usize>)> {
+    if !ITEM_CHANGE_EVENTS.contains(&event.event_name.as_str()) {
+        return None;
+    }
+
+    let data = event.data.as_ref()?;
+
+    let identifier = serde_json::from_value(data.get("Identifier")?.clone()).ok()?;
+    let index = data.get("Index")
+        .and_then(|index| index.as_u64())
+        .map(|index| index as usize);
+
+    Some((identifier, index))
+}
+
+/// Item changes merged per device, so a burst of events only causes one refresh
+#[derive(Default)]
+struct ItemChanges {
+    /// `None` if the whole screen changed
+    pending: HashMap<NamespacedDeviceIdentifier, Option<BTreeSet<usize>>>,
+    deadline: Option<Instant>
+}
 
-    ui_main(ui_tx, api_rx, waiter)
+impl ItemChanges {
+    fn add(&mut self, identifier: NamespacedDeviceIdentifier, index: Option<usize>) {
+        let indices = self.pending.entry(identifier)
+            .or_insert_with(|| Some(BTreeSet::new()));
+
+        match (indices.as_mut(), index) {
+            (Some(indices), Some(index)) => {
+                indices.insert(index);
+            }
+            _ => *indices = None
+        }
+
+        self.deadline.get_or_insert_with(|| Instant::now() + ITEM_CHANGE_DELAY);
+    }
+
+    /// Resolves once collected changes should be sent, never if there's none
+    async fn elapsed(&self) {
+        match self.deadline {
+            Some(deadline) => sleep_until(deadline).await,
+            None => pending().await
+        }
+    }
+
+    fn take(&mut self) -> Vec<(NamespacedDeviceIdentifier, Option<Vec<usize>>)> {
+        self.deadline = None;
+
+        self.pending.drain()
+            .map(|(identifier, indices)| (identifier, indices.map(|indices| indices.into_iter().collect())))
+            .collect()
+    }
+}
+
+/// Retrieves screen stack and items of device's current screen
+async fn refresh_screen(device: &DeviceHandle, api_tx: &APISender) -> Result<(), Failure> {
+    let stack = device.screen_stack().await
+        .map_err(failure("Failed to get screen stack"))?;
+    api_tx.send(APIMessage::Stack { identifier: device.identifier().clone(), stack }).await;
+
+    let items = device.items(Some(true)).await
+        .map_err(failure("Failed to get screen items"))?;
+    api_tx.send(APIMessage::ScreenItems { identifier: device.identifier().clone(), items }).await;
+
+    Ok(())
+}
+
+/// Sends messages to the UI and wakes it up to process them
+#[derive(Clone)]
+pub struct APISender {
+    messages: mpsc::Sender<APIMessage>,
+    waker: mpsc::Sender<()>
+}
+
+impl APISender {
+    pub async fn send(&self, message: APIMessage) {
+        if self.messages.send(message).await.is_ok() {
+            // Waker holds a single wake up, so a burst of messages only causes one repaint
+            self.waker.try_send(()).ok();
+        }
+    }
+
+    pub async fn notify(&self, notification: Notification) {
+        self.send(APIMessage::Notification(notification)).await;
+    }
+
+    /// Reports failed operation to the user
+    pub async fn error(&self, title: &str, error: impl Display) {
+        self.notify(Notification::error(title).with_details(error)).await;
+    }
 }
 
 pub enum APIMessage {
+    ConnectionInfo(ConnectionInfo),
+    ConnectionState {
+        url: String,
+        state: ConnectionState
+    },
+
     DeviceList(Vec<Device>),
 
-    NewDevice(Device),
-    DeviceGone(NamespacedDeviceIdentifier),
+    /// Device responses are ignored by the UI if another device was opened in the meantime
+    InputGrid {
+        identifier: NamespacedDeviceIdentifier,
+        grid: Vec<Input>
+    },
+    Stack {
+        identifier: NamespacedDeviceIdentifier,
+        stack: Vec<String>
+    },
+    ScreenItems {
+        identifier: NamespacedDeviceIdentifier,
+        items: Vec<Option<PartialScreenItem>>
+    },
+    ItemProperties {
+        identifier: NamespacedDeviceIdentifier,
+        input: usize,
+        properties: Option<ItemProperties>
+    },
+    /// Items of device's current screen changed, `None` if the whole screen did
+    ItemChanged {
+        identifier: NamespacedDeviceIdentifier,
+        indices: Option<Vec<usize>>
+    },
+
+    Notification(Notification),
+    /// Operation with the id has finished, failure details are reported with a notification
+    Completed {
+        id: OperationId,
+        success: bool
+    }
+}
+#[cfg(test)]
+mod tests {
+    use streamduck_rust_client::base::{DeviceIdentifier, NamespacedDeviceIdentifier, NamespacedName};
+    use super::ItemChanges;
+
+    fn device(identifier: &str) -> NamespacedDeviceIdentifier {
+        NamespacedDeviceIdentifier {
+            name: NamespacedName::new("test", "driver"),
+            identifier: DeviceIdentifier::new(identifier, "Test device")
+        }
+    }
+
+    fn sorted(changes: &mut ItemChanges) -> Vec<(NamespacedDeviceIdentifier, Option<Vec<usize>>)> {
+        let mut taken = changes.take();
+        taken.sort_by(|(a, _), (b, _)| a.identifier.identifier.cmp(&b.identifier.identifier));
+        taken
+    }
+
+    #[test]
+    fn events_for_device_collapse() {
+        let mut changes = ItemChanges::default();
+
+        changes.add(device("a"), Some(3));
+        changes.add(device("a"), Some(1));
+        changes.add(device("a"), Some(3));
 
-    ConnectedDevice(Device),
-    DisconnectedDevice(NamespacedDeviceIdentifier),
+        assert_eq!(sorted(&mut changes), vec![(device("a"), Some(vec![1, 3]))]);
+    }
 
-    InputGrid(Vec<Input>),
-    Stack(Vec<String>),
-    ScreenItems(Vec<Option<PartialScreenItem>>)
-}
\ No newline at end of file
+    #[test]
+    fn screen_change_covers_items() {
+        let mut changes = ItemChanges::default();
+
+        changes.add(device("a"), Some(3));
+        changes.add(device("a"), None);
+        changes.add(device("a"), Some(1));
+
+        assert_eq!(sorted(&mut changes), vec![(device("a"), None)]);
+    }
+
+    #[test]
+    fn devices_stay_separate() {
+        let mut changes = ItemChanges::default();
+
+        changes.add(device("a"), Some(0));
+        changes.add(device("b"), None);
+        changes.add(device("a"), Some(2));
+
+        assert_eq!(sorted(&mut changes), vec![
+            (device("a"), Some(vec![0, 2])),
+            (device("b"), None)
+        ]);
+    }
+
+    #[test]
+    fn take_resets() {
+        let mut changes = ItemChanges::default();
+
+        changes.add(device("a"), Some(0));
+        assert!(changes.deadline.is_some());
+
+        changes.take();
+        assert!(changes.deadline.is_none());
+        assert!(changes.take().is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use directories_next::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+/// Saved daemon address
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ConnectionProfile {
+    pub name: String,
+    pub url: String
+}
+
+/// Connection profiles stored in a JSON file
+#[derive(Default)]
+pub struct ProfileList {
+    path: Option<PathBuf>,
+    profiles: Vec<ConnectionProfile>
+}
+
+impl ProfileList {
+    /// File in user's config folder
+    pub fn default_path() -> Option<PathBuf> {
+        ProjectDirs::from("", "", "Streamduck")
+            .map(|dirs| dirs.config_dir().join("connections.json"))
+    }
+
+    /// Loads profiles from the file, starts empty if the file is missing or broken
+    ///
+    /// Error is only returned for a broken file, missing file just means there's no profiles yet
+    pub fn load(path: Option<PathBuf>) -> (Self, Option<serde_json::Error>) {
+        let result = path.as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|json| serde_json::from_str(&json));
+
+        let (profiles, error) = match result {
+            Some(Ok(profiles)) => (profiles, None),
+            Some(Err(error)) => (vec![], Some(error)),
+            None => (vec![], None)
+        };
+
+        (Self { path, profiles }, error)
+    }
+
+    pub fn profiles(&self) -> &[ConnectionProfile] {
+        &self.profiles
+    }
+
+    /// Adds the profile, replacing profile with the same name
+    pub fn save(&mut self, profile: ConnectionProfile) -> io::Result<()> {
+        match self.profiles.iter_mut().find(|existing| existing.name == profile.name) {
+            Some(existing) => *existing = profile,
+            None => self.profiles.push(profile)
+        }
+
+        self.write()
+    }
+
+    pub fn remove(&mut self, index: usize) -> io::Result<()> {
+        if index < self.profiles.len() {
+            self.profiles.remove(index);
+        }
+
+        self.write()
+    }
+
+    fn write(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "No file to store profiles in"));
+        };
+
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+
+        fs::write(path, serde_json::to_string_pretty(&self.profiles)?)
+    }
+}
@@ -0,0 +1,266 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use directories_next::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use streamduck_rust_client::api::ItemProperties;
+
+/// Reusable screen item configuration
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Template {
+    pub name: String,
+    pub properties: ItemProperties,
+    /// Base64 encoded JPEG preview of the item the template was saved from
+    #[serde(default, rename = "Base64JPG")]
+    pub preview: Option<String>
+}
+
+pub struct StoredTemplate {
+    pub path: PathBuf,
+    pub template: Template
+}
+
+/// Templates stored as JSON files in a directory
+#[derive(Default)]
+pub struct TemplateLibrary {
+    directory: Option<PathBuf>,
+    templates: Vec<StoredTemplate>
+}
+
+impl TemplateLibrary {
+    /// Directory in user's config folder
+    pub fn default_directory() -> Option<PathBuf> {
+        ProjectDirs::from("", "", "Streamduck")
+            .map(|dirs| dirs.config_dir().join("templates"))
+    }
+
+    /// Loads all templates from the directory, broken files are skipped and returned with their errors
+    pub fn load(directory: Option<PathBuf>) -> (Self, Vec<(PathBuf, io::Error)>) {
+        let mut library = Self {
+            directory,
+            templates: vec![]
+        };
+        let mut failed = vec![];
+
+        let Some(entries) = library.directory.as_ref().and_then(|directory| fs::read_dir(directory).ok()) else {
+            return (library, failed);
+        };
+
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_some_and(|extension| extension == "json") {
+                match read_template(&path) {
+                    Ok(template) => library.templates.push(StoredTemplate { path, template }),
+                    Err(error) => failed.push((path, error))
+                }
+            }
+        }
+
+        library.sort();
+        (library, failed)
+    }
+
+    pub fn templates(&self) -> &[StoredTemplate] {
+        &self.templates
+    }
+
+    /// Template stored in the file
+    pub fn get(&self, path: &Path) -> Option<&StoredTemplate> {
+        self.templates.iter().find(|stored| stored.path == path)
+    }
+
+    /// Templates whose name contains the query, ignoring case
+    pub fn search<'a>(&'a self, query: &str) -> impl Iterator<Item = &'a StoredTemplate> {
+        let query = query.trim().to_lowercase();
+
+        self.templates.iter()
+            .filter(move |stored| stored.template.name.to_lowercase().contains(&query))
+    }
+
+    /// Writes the template to disk, replacing template with the same name
+    pub fn save(&mut self, template: Template) -> io::Result<()> {
+        let Some(directory) = self.directory.clone() else {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "No directory to store templates in"));
+        };
+
+        fs::create_dir_all(&directory)?;
+
+        let path = match self.templates.iter().find(|stored| stored.template.name == template.name) {
+            Some(existing) => existing.path.clone(),
+            None => self.unused_path(&directory, &file_name(&template.name))
+        };
+
+        let json = serde_json::to_string_pretty(&template)?;
+        fs::write(&path, json)?;
+
+        self.templates.retain(|stored| stored.path != path);
+        self.templates.push(StoredTemplate { path, template });
+        self.sort();
+
+        Ok(())
+    }
+
+    /// Deletes the template from disk
+    pub fn remove(&mut self, path: &Path) -> io::Result<()> {
+        if self.get(path).is_none() {
+            return Ok(());
+        }
+
+        fs::remove_file(path)?;
+        self.templates.retain(|stored| stored.path != path);
+
+        Ok(())
+    }
+
+    /// Different names can end up with the same file name, so a number is added until it's free
+    fn unused_path(&self, directory: &Path, file_name: &str) -> PathBuf {
+        let mut path = directory.join(format!("{file_name}.json"));
+        let mut number = 2;
+
+        while path.exists() || self.get(&path).is_some() {
+            path = directory.join(format!("{file_name}_{number}.json"));
+            number += 1;
+        }
+
+        path
+    }
+
+    fn sort(&mut self) {
+        self.templates.sort_by_key(|stored| stored.template.name.to_lowercase());
+    }
+}
+
+fn read_template(path: &PathBuf) -> io::Result<Template> {
+    let json = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Turns template name into something that's safe to use as a file name
+fn file_name(name: &str) -> String {
+    let name: String = name.trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+
+    if name.is_empty() {
+        "template".to_string()
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+    use super::{file_name, Template, TemplateLibrary};
+
+    fn template(name: &str) -> Template {
+        Template {
+            name: name.to_string(),
+            properties: Default::default(),
+            preview: None
+        }
+    }
+
+    fn library() -> (TempDir, TemplateLibrary) {
+        let directory = TempDir::new().unwrap();
+        let (library, failed) = TemplateLibrary::load(Some(directory.path().to_path_buf()));
+        assert!(failed.is_empty());
+
+        (directory, library)
+    }
+
+    fn file_names(library: &TemplateLibrary) -> Vec<String> {
+        let mut names: Vec<String> = library.templates().iter()
+            .map(|stored| stored.path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn names_are_sanitised() {
+        assert_eq!(file_name("Volume Up"), "Volume_Up");
+        assert_eq!(file_name("  ../secret  "), "___secret");
+        assert_eq!(file_name("ключ-1_a"), "ключ-1_a");
+        assert_eq!(file_name("   "), "template");
+    }
+
+    #[test]
+    fn colliding_names_get_separate_files() {
+        let (_directory, mut library) = library();
+
+        library.save(template("Mute mic")).unwrap();
+        library.save(template("Mute/mic")).unwrap();
+        library.save(template("Mute?mic")).unwrap();
+
+        assert_eq!(file_names(&library), vec!["Mute_mic.json", "Mute_mic_2.json", "Mute_mic_3.json"]);
+    }
+
+    #[test]
+    fn existing_file_is_not_overwritten() {
+        let (directory, mut library) = library();
+        let foreign = directory.path().join("Notes.json");
+        fs::write(&foreign, "not a template").unwrap();
+
+        library.save(template("Notes")).unwrap();
+
+        assert_eq!(fs::read_to_string(&foreign).unwrap(), "not a template");
+        assert_eq!(file_names(&library), vec!["Notes_2.json"]);
+    }
+
+    #[test]
+    fn same_name_replaces_template() {
+        let (directory, mut library) = library();
+
+        library.save(template("Play")).unwrap();
+
+        let mut changed = template("Play");
+        changed.properties.renderable = true;
+        library.save(changed.clone()).unwrap();
+
+        assert_eq!(library.templates().len(), 1);
+
+        let (reloaded, _) = TemplateLibrary::load(Some(directory.path().to_path_buf()));
+        assert_eq!(reloaded.templates()[0].template, changed);
+    }
+
+    #[test]
+    fn templates_are_removed_by_path() {
+        let (_directory, mut library) = library();
+
+        library.save(template("First")).unwrap();
+        library.save(template("Second")).unwrap();
+
+        let path = library.templates()[0].path.clone();
+        library.remove(&path).unwrap();
+
+        assert!(!path.exists());
+        assert_eq!(file_names(&library), vec!["Second.json"]);
+
+        // Already gone
+        library.remove(&path).unwrap();
+        library.remove(&PathBuf::from("missing.json")).unwrap();
+    }
+
+    #[test]
+    fn broken_files_are_reported() {
+        let directory = TempDir::new().unwrap();
+        fs::write(directory.path().join("broken.json"), "{").unwrap();
+        fs::write(directory.path().join("ignored.txt"), "{").unwrap();
+
+        let (library, failed) = TemplateLibrary::load(Some(directory.path().to_path_buf()));
+
+        assert!(library.templates().is_empty());
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].0, directory.path().join("broken.json"));
+    }
+}
@@ -0,0 +1,92 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use egui::{Button, Color32, Frame, RichText, Spinner, Ui, vec2};
+use tokio::sync::mpsc::Sender;
+use streamduck_rust_client::connection::ConnectionState;
+use crate::ui::{UIMessage, UIRequest};
+use crate::ui::util::send_ui_message;
+
+pub struct ConnectionStatus {
+    pub url: String,
+    pub state: ConnectionState
+}
+
+pub enum ConnectionScreenResponse {
+    OpenSettings,
+    Nothing
+}
+
+pub fn connection_screen(ui: &mut Ui, status: &ConnectionStatus, sender: &Sender<UIRequest>) -> ConnectionScreenResponse {
+    let mut response = ConnectionScreenResponse::Nothing;
+
+    Frame::default()
+        .fill(Color32::from_rgb(40, 40, 40))
+        .rounding(10.0)
+        .inner_margin(10.0)
+        .show(ui, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.heading(RichText::new("Connecting to Daemon")
+                    .size(36.0));
+            });
+        });
+
+    ui.add_space(2.0);
+
+    Frame::default()
+        .fill(Color32::from_rgb(40, 40, 40))
+        .rounding(10.0)
+        .inner_margin(10.0)
+        .show(ui, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(20.0);
+
+                let (state, error) = match &status.state {
+                    ConnectionState::Connecting => ("Connecting...".to_string(), None),
+                    ConnectionState::Connected => ("Connected".to_string(), None),
+                    ConnectionState::Reconnecting { attempt, last_error } => (
+                        format!("Couldn't reach the daemon, trying again (attempt {attempt})"),
+                        last_error.as_deref()
+                    ),
+                    ConnectionState::Closed { reason } => (
+                        "Gave up on connecting".to_string(),
+                        reason.as_deref()
+                    )
+                };
+
+                if status.state.is_closed() {
+                    ui.label(RichText::new("\u{f0026}").size(64.0));
+                } else {
+                    ui.add(Spinner::new().size(64.0));
+                }
+                ui.add_space(10.0);
+
+                ui.label(RichText::new(state).size(18.0));
+                ui.label(RichText::new(&status.url).monospace());
+
+                if let Some(error) = error {
+                    ui.add_space(10.0);
+                    ui.label(RichText::new(error).color(ui.style().visuals.error_fg_color));
+                }
+
+                ui.add_space(10.0);
+                ui.label("Make sure Streamduck daemon is running, the GUI will connect as soon as it's reachable");
+
+                ui.add_space(10.0);
+                if ui.add(Button::new("Retry now").min_size(vec2(120.0, 30.0)).rounding(8.0)).clicked() {
+                    send_ui_message(sender, UIMessage::RetryConnection);
+                }
+
+                if ui.add(Button::new("Connection settings").min_size(vec2(120.0, 30.0)).rounding(8.0)).clicked() {
+                    response = ConnectionScreenResponse::OpenSettings;
+                }
+            });
+
+            ui.allocate_space(ui.available_size());
+        });
+
+    response
+}
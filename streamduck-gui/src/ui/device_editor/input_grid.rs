@@ -4,14 +4,18 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
-use egui::{Color32, Id, pos2, Rangef, Rect, Sense, Spinner, Stroke, Ui, vec2};
+use egui::{Align2, Button, Color32, FontId, Id, Key, pos2, Pos2, Rangef, Rect, Sense, Spinner, Stroke, Ui, vec2, Vec2};
 use interpolation::Ease;
 
 use tokio::sync::mpsc::Sender;
 use streamduck_rust_client::api::{Input, InputIcon};
-use crate::ui::{UIMessage, UIState};
+use crate::ui::{UIMessage, UIRequest, UIState};
+use crate::ui::device_editor::previews::paint_preview;
 use crate::ui::device_editor::stack::stack_line;
-use crate::ui::util::send_ui_message;
+use crate::ui::device_editor::templates::TemplateDrag;
+use crate::ui::device_editor::properties::is_saving;
+use crate::ui::operations::PendingOperation;
+use crate::ui::util::{interact_lerped_selectable, lerp_color, send_ui_message};
 
 #[derive(Default, Debug)]
 pub struct Grid {
@@ -77,9 +81,88 @@ impl Grid {
 
         grid
     }
+
+    /// Closest input in the direction from the input, for keyboard navigation
+    pub fn neighbour(&self, from: usize, direction: Vec2) -> Option<usize> {
+        let center = self.items.get(from)?.center();
+
+        self.items.iter()
+            .enumerate()
+            .filter(|(index, _)| *index != from)
+            .filter_map(|(index, item)| {
+                let offset = item.center() - center;
+                let along = offset.dot(direction);
+                let across = (offset - direction * along).length();
+
+                // Preferring inputs that are in line with the current one
+                (along > 0.0).then_some((index, along + across * 2.0))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(index, _)| index)
+    }
+}
+
+impl GridItem {
+    fn center(&self) -> Pos2 {
+        pos2(self.x + self.w / 2.0, self.y + self.h / 2.0)
+    }
+
+    fn tooltip(&self) -> String {
+        format!(
+            "{:?}\nPosition: {}, {}\nSize: {} x {}",
+            self.looks.icon,
+            self.looks.x, self.looks.y,
+            self.looks.w, self.looks.h
+        )
+    }
+}
+
+fn keyboard_navigation(ui: &Ui, state: &mut UIState) {
+    // Text fields and other widgets need the keys more
+    if ui.memory(|memory| memory.focused().is_some()) {
+        return;
+    }
+
+    let (direction, shift, escape) = ui.input(|input| {
+        let direction = [
+            (Key::ArrowLeft, vec2(-1.0, 0.0)),
+            (Key::ArrowRight, vec2(1.0, 0.0)),
+            (Key::ArrowUp, vec2(0.0, -1.0)),
+            (Key::ArrowDown, vec2(0.0, 1.0))
+        ].into_iter()
+            .find(|(key, _)| input.key_pressed(*key))
+            .map(|(_, direction)| direction);
+
+        (direction, input.modifiers.shift, input.key_pressed(Key::Escape))
+    });
+
+    let editor = &mut state.device_editor;
+
+    if escape {
+        editor.selection.clear();
+    }
+
+    let (Some(direction), Some(grid)) = (direction, &editor.grid) else {
+        return;
+    };
+
+    let Some(focus) = editor.selection.focus() else {
+        if !grid.items.is_empty() {
+            editor.selection.select_only(0);
+        }
+        return;
+    };
+
+    if let Some(next) = grid.neighbour(focus, direction) {
+        if shift {
+            editor.selection.add(next);
+        } else {
+            editor.selection.select_only(next);
+        }
+    }
 }
 
-pub fn input_grid(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage>, collapsed: bool, inner_ui: impl FnOnce(&mut Ui, &mut UIState)) {
+pub fn input_grid(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIRequest>, collapsed: bool, inner_ui: impl FnOnce(&mut Ui, &mut UIState)) {
     // No spacing
     let old_spacing = ui.spacing().item_spacing;
     ui.spacing_mut().item_spacing.x = 0.0;
@@ -140,6 +223,8 @@ pub fn input_grid(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage>,
 
     // Actual input grid and stack
     if left_width > draw_threshold {
+        keyboard_navigation(ui, state);
+
         if let Some(grid) = &state.device_editor.grid {
             let aspected_height = theoretical_left_width / grid.width_to_height_ratio;
             let expected_height = f32::min(aspected_height, left_rect.height());
@@ -154,7 +239,7 @@ pub fn input_grid(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage>,
 
             let grid_rect = Rect::from_center_size(center, vec2(expected_width, expected_height));
 
-            for item in &grid.items {
+            for (index, item) in grid.items.iter().enumerate() {
                 let id = Id::new(&item.looks);
 
                 let min = pos2(
@@ -179,18 +264,93 @@ pub fn input_grid(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage>,
                     | InputIcon::Trackball | InputIcon::Sensor => 10000.0,
                 } * scale;
 
-                let response = ui.interact(item_rect, id, Sense::click());
-                let style = ui.style().interact(&response);
+                let response = ui.interact(item_rect, id, Sense::click())
+                    .on_hover_text(item.tooltip());
+
+                if response.clicked() {
+                    let modifiers = ui.input(|input| input.modifiers);
+                    state.device_editor.selection.click(index, modifiers);
+                }
+
+                let template_hovered = response.dnd_hover_payload::<TemplateDrag>().is_some();
+
+                if let Some(drag) = response.dnd_release_payload::<TemplateDrag>() {
+                    if let Some(stored) = state.templates.get(&drag.0) {
+                        let id = send_ui_message(sender, UIMessage::SetItemProperties {
+                            identifier: state.device_editor.device.clone(),
+                            input: index,
+                            properties: stored.template.properties.clone()
+                        });
+
+                        state.operations.start(id, PendingOperation::SaveProperties {
+                            identifier: state.device_editor.device.clone(),
+                            input: index,
+                            properties: stored.template.properties.clone()
+                        });
+
+                        state.device_editor.properties.discard(index);
+                        state.device_editor.selection.select_only(index);
+                    }
+                }
+
+                let selected = state.device_editor.selection.contains(index) || template_hovered;
+                let style = interact_lerped_selectable(ui, &response, selected, id.with("selected"), 0.15);
 
                 ui.painter().rect(item_rect, rounding, style.bg_fill, style.bg_stroke);
+
+                if let Some(preview) = state.device_editor.items.get(index) {
+                    paint_preview(ui, item_rect.shrink(style.bg_stroke.width), rounding, preview, style.fg_stroke.color);
+                }
+
+                // Saving indicator, or unsaved changes marker
+                if is_saving(&state.operations, &state.device_editor.device, index) {
+                    let size = (item_rect.width().min(item_rect.height()) * 0.2).max(10.0);
+                    Spinner::new().paint_at(ui, Rect::from_center_size(
+                        item_rect.right_top() + vec2(-size, size),
+                        vec2(size, size)
+                    ));
+                } else if state.device_editor.properties.is_dirty(index) {
+                    let radius = (item_rect.width().min(item_rect.height()) * 0.06).max(3.0);
+                    ui.painter().circle_filled(
+                        item_rect.right_top() + vec2(-radius * 2.0, radius * 2.0),
+                        radius,
+                        ui.style().visuals.warn_fg_color
+                    );
+                }
+
+                // Selection outline
+                let outline = ui.ctx().animate_bool_with_time(id.with("outline"), selected, 0.15);
+                if outline > 0.0 {
+                    ui.painter().rect_stroke(
+                        item_rect.expand(gap),
+                        rounding,
+                        Stroke::new(2.0, lerp_color(&Color32::TRANSPARENT, &style.bg_fill, outline))
+                    );
+                }
+            }
+        } else if state.device_editor.grid_failed {
+            ui.painter().text(
+                left_rect.center() - vec2(0.0, 30.0),
+                Align2::CENTER_CENTER,
+                "Couldn't retrieve device's inputs",
+                FontId::proportional(13.0),
+                ui.style().visuals.error_fg_color
+            );
+
+            let retry = ui.put(Rect::from_center_size(left_rect.center(), vec2(120.0, 30.0)), Button::new("Retry").rounding(8.0));
+            if retry.clicked() {
+                state.device_editor.grid_failed = false;
             }
         } else {
             let spinner = Spinner::new();
             spinner.paint_at(ui, Rect::from_center_size(left_rect.center(), vec2(75.0, 75.0)));
 
-            if !state.device_editor.waiting_for_grid {
-                state.device_editor.waiting_for_grid = true;
-                send_ui_message(sender, UIMessage::GetDeviceState(state.device_editor.device.clone()));
+            let identifier = state.device_editor.device.clone();
+            let waiting = state.operations.any(|operation| operation == &PendingOperation::GetDeviceState(identifier.clone()));
+
+            if !waiting {
+                let id = send_ui_message(sender, UIMessage::GetDeviceState(identifier.clone()));
+                state.operations.start(id, PendingOperation::GetDeviceState(identifier));
             }
         }
     }
@@ -230,4 +390,57 @@ pub fn input_grid(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage>,
 
     // Restore spacing
     ui.spacing_mut().item_spacing = old_spacing;
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use egui::vec2;
+    use streamduck_rust_client::api::{Input, InputIcon};
+    use super::Grid;
+
+    /// 3 x 2 grid of buttons, indices go left to right, then top to bottom
+    fn grid() -> Grid {
+        Grid::from_inputs((0..6)
+            .map(|index| Input { x: index % 3, y: index / 3, w: 1, h: 1, icon: InputIcon::Button })
+            .collect())
+    }
+
+    #[test]
+    fn neighbour_moves_in_direction() {
+        let grid = grid();
+
+        assert_eq!(grid.neighbour(0, vec2(1.0, 0.0)), Some(1));
+        assert_eq!(grid.neighbour(0, vec2(0.0, 1.0)), Some(3));
+        assert_eq!(grid.neighbour(4, vec2(-1.0, 0.0)), Some(3));
+        assert_eq!(grid.neighbour(4, vec2(0.0, -1.0)), Some(1));
+    }
+
+    #[test]
+    fn neighbour_stops_at_edges() {
+        let grid = grid();
+
+        assert_eq!(grid.neighbour(0, vec2(-1.0, 0.0)), None);
+        assert_eq!(grid.neighbour(0, vec2(0.0, -1.0)), None);
+        assert_eq!(grid.neighbour(2, vec2(1.0, 0.0)), None);
+        assert_eq!(grid.neighbour(5, vec2(0.0, 1.0)), None);
+    }
+
+    #[test]
+    fn neighbour_prefers_inputs_in_line() {
+        // Wide input on the bottom row, below both top inputs
+        let grid = Grid::from_inputs(vec![
+            Input { x: 0, y: 0, w: 1, h: 1, icon: InputIcon::Button },
+            Input { x: 1, y: 0, w: 1, h: 1, icon: InputIcon::Button },
+            Input { x: 3, y: 0, w: 1, h: 1, icon: InputIcon::Button },
+            Input { x: 0, y: 1, w: 2, h: 1, icon: InputIcon::Slider }
+        ]);
+
+        assert_eq!(grid.neighbour(1, vec2(1.0, 0.0)), Some(2));
+        assert_eq!(grid.neighbour(3, vec2(0.0, -1.0)), Some(0));
+        assert_eq!(grid.neighbour(2, vec2(0.0, 1.0)), Some(3));
+    }
+
+    #[test]
+    fn neighbour_of_unknown_input_is_none() {
+        assert_eq!(grid().neighbour(10, vec2(1.0, 0.0)), None);
+    }
+}
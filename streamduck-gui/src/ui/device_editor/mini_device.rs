@@ -4,11 +4,10 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
-use egui::{Color32, FontFamily, FontId, Id, pos2, Rect, Sense, Stroke, Ui, vec2};
-use egui::epaint::TextShape;
+use egui::{Color32, FontFamily, FontId, Id, pos2, Ui, vec2};
 use streamduck_rust_client::base::NamespacedDeviceIdentifier;
-use interpolation::{Lerp, Ease};
-use crate::ui::util::{interact_lerped_selectable, lerp_color, lerped_selectable};
+use interpolation::Ease;
+use crate::ui::util::{lerp_color, lerped_selectable};
 
 pub fn mini_device_button(ui: &mut Ui, device: &NamespacedDeviceIdentifier, connected: bool, how_much_to_leave: f32) {
     // Device name galleys
@@ -65,7 +64,7 @@ pub fn mini_device_button(ui: &mut Ui, device: &NamespacedDeviceIdentifier, conn
         1000.0
     );
 
-    let connected_text_gap = (element_rect.height() / 2.0 - connected_galley.rect.height() / 2.0);
+    let connected_text_gap = element_rect.height() / 2.0 - connected_galley.rect.height() / 2.0;
 
     ui.painter().galley(
         pos2(
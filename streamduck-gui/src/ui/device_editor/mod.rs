@@ -6,18 +6,28 @@
 
 pub mod mini_device;
 pub mod input_grid;
-mod properties;
-mod tabs;
+pub mod properties;
+pub mod tabs;
+pub mod templates;
 mod stack;
+pub mod previews;
+pub mod texture_cache;
+pub mod selection;
 
-use egui::{Align, Button, Color32, Frame, Layout, Margin, RichText, Rounding, ScrollArea, SidePanel, TopBottomPanel, Ui, vec2};
+use egui::{Align, Button, Color32, Frame, Layout, RichText, Rounding, TopBottomPanel, Ui, vec2};
+use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::Sender;
-use streamduck_rust_client::api::Input;
+use streamduck_rust_client::api::PartialScreenItem;
 use streamduck_rust_client::base::NamespacedDeviceIdentifier;
-use crate::ui::{Pages, UIMessage, UIState};
+use crate::ui::{Pages, UIRequest, UIState};
 use crate::ui::device_editor::input_grid::{Grid, input_grid};
 use crate::ui::device_editor::mini_device::mini_device_button;
-use crate::ui::device_editor::properties::properties_ui;
+use crate::ui::device_editor::previews::ItemPreview;
+use crate::ui::device_editor::selection::Selection;
+use crate::ui::device_editor::tabs::Tab;
+use crate::ui::device_editor::templates::TemplatesTab;
+use crate::ui::device_editor::texture_cache::TextureCache;
+use crate::ui::device_editor::properties::{properties_ui, PropertiesEditor};
 
 #[derive(Default)]
 pub struct DeviceEditor {
@@ -25,11 +35,44 @@ pub struct DeviceEditor {
     pub connected: bool,
     pub grid: Option<Grid>,
     pub stack: Vec<String>,
-    pub waiting_for_grid: bool,
+    pub items: Vec<ItemPreview>,
+    pub screen_items: Vec<Option<PartialScreenItem>>,
+    pub texture_cache: TextureCache,
+    pub selection: Selection,
+    pub properties: PropertiesEditor,
+    pub tab: EditorTab,
+    pub templates: TemplatesTab,
+    /// Input layout couldn't be retrieved, it's requested again once user retries
+    pub grid_failed: bool,
     pub grid_collapsed: bool
 }
 
-pub fn device_editor(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage>) {
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, Eq, PartialEq)]
+pub enum EditorTab {
+    #[default]
+    Properties,
+    Templates
+}
+
+impl EditorTab {
+    pub const ALL: [EditorTab; 2] = [EditorTab::Properties, EditorTab::Templates];
+
+    pub fn tab(&self) -> Tab {
+        match self {
+            EditorTab::Properties => Tab::new("Properties").with_icon("\u{f013}"),
+            EditorTab::Templates => Tab::new("Templates").with_icon("\u{f0c5}")
+        }
+    }
+}
+
+impl DeviceEditor {
+    /// Depth of device's current screen in the stack
+    pub fn depth(&self) -> usize {
+        self.stack.len().saturating_sub(1)
+    }
+}
+
+pub fn device_editor(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIRequest>) {
     TopBottomPanel::top("editor-top")
         .frame(Frame::default()
             .inner_margin(10.0)
@@ -57,7 +100,7 @@ pub fn device_editor(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage
                 if ui.add(Button::new(RichText::new("\u{f0493}").size(30.0).line_height(Some(32.0)))
                     .min_size(vec2(button_width, 50.0))
                     .rounding(8.0)).clicked() {
-
+                    state.device_editor.grid_collapsed = !state.device_editor.grid_collapsed;
                 }
             });
         });
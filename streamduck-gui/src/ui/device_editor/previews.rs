@@ -0,0 +1,96 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use egui::{Align2, Color32, ColorImage, Context, FontId, Image, Rect, Stroke, TextureHandle, Ui};
+use streamduck_rust_client::api::PartialScreenItem;
+use streamduck_rust_client::preview::PreviewError;
+use crate::ui::device_editor::texture_cache::{PreviewKey, TextureCache};
+
+pub enum ItemPreview {
+    /// Nothing is assigned to the input
+    Empty,
+    /// Item isn't drawn onto the device
+    NotRenderable,
+    /// Item is drawn, but preview couldn't be decoded
+    Unavailable,
+    /// Decoded preview, uploaded as a texture
+    Image(TextureHandle)
+}
+
+impl ItemPreview {
+    /// Returns [ItemPreview::Unavailable] along with the error if the preview couldn't be decoded,
+    /// error is only returned the first time the same preview fails
+    pub fn load(ctx: &Context, cache: &mut TextureCache, key: PreviewKey, item: Option<PartialScreenItem>) -> (Self, Option<PreviewError>) {
+        let Some(item) = item else {
+            cache.invalidate(&key);
+            return (ItemPreview::Empty, None);
+        };
+
+        if !item.renderable {
+            cache.invalidate(&key);
+            return (ItemPreview::NotRenderable, None);
+        }
+
+        let Some(encoded) = item.base64jpg.as_deref() else {
+            cache.invalidate(&key);
+            return (ItemPreview::Unavailable, None);
+        };
+
+        let mut error = None;
+        let texture = cache.get_or_load(ctx, key, encoded, || {
+            decode_preview(&item).map_err(|decode_error| error = Some(decode_error)).ok()
+        });
+
+        (texture.map_or(ItemPreview::Unavailable, ItemPreview::Image), error)
+    }
+}
+
+pub fn decode_preview(item: &PartialScreenItem) -> Result<ColorImage, PreviewError> {
+    let preview = item.decode_preview()?;
+
+    Ok(ColorImage::from_rgba_unmultiplied(
+        [preview.width as usize, preview.height as usize],
+        &preview.rgba
+    ))
+}
+
+pub fn paint_preview(ui: &Ui, rect: Rect, rounding: f32, preview: &ItemPreview, color: Color32) {
+    let placeholder_color = color.gamma_multiply(0.5);
+    let placeholder_size = rect.height().min(rect.width()) * 0.4;
+
+    match preview {
+        ItemPreview::Empty => {
+            ui.painter().text(
+                rect.center(),
+                Align2::CENTER_CENTER,
+                "+",
+                FontId::proportional(placeholder_size),
+                placeholder_color
+            );
+        }
+
+        ItemPreview::NotRenderable => {
+            let inner = rect.shrink(rect.width().min(rect.height()) * 0.3);
+            ui.painter().line_segment([inner.left_bottom(), inner.right_top()], Stroke::new(2.0, placeholder_color));
+        }
+
+        ItemPreview::Unavailable => {
+            ui.painter().text(
+                rect.center(),
+                Align2::CENTER_CENTER,
+                "?",
+                FontId::proportional(placeholder_size),
+                placeholder_color
+            );
+        }
+
+        ItemPreview::Image(texture) => {
+            Image::new(texture)
+                .rounding(rounding)
+                .paint_at(ui, rect);
+        }
+    }
+}
@@ -4,20 +4,117 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
-use std::fmt::format;
-use egui::{Color32, Frame, Margin, pos2, Rect, ScrollArea, Ui, vec2};
+use std::collections::{HashMap, HashSet};
+use egui::{Button, Color32, DragValue, pos2, Rect, RichText, ScrollArea, TextEdit, Ui, vec2};
+use serde_json::{Number, Value};
 use tokio::sync::mpsc::Sender;
-use crate::ui::{UIMessage, UIState};
-use crate::ui::device_editor::tabs::tabs;
+use streamduck_rust_client::api::ItemProperties;
+use streamduck_rust_client::base::NamespacedDeviceIdentifier;
+use crate::ui::{UIMessage, UIRequest, UIState};
+use crate::ui::device_editor::previews::ItemPreview;
+use crate::ui::device_editor::EditorTab;
+use crate::ui::device_editor::tabs::{TabResponse, tabs};
+use crate::ui::device_editor::templates::templates_ui;
+use crate::ui::operations::{Operations, PendingOperation};
+use crate::ui::util::send_ui_message;
 
-pub fn properties_ui(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage>) {
+/// Properties of current screen's items, with edits that weren't saved yet
+#[derive(Default)]
+pub struct PropertiesEditor {
+    /// Properties as they are in the daemon, by input index
+    loaded: HashMap<usize, Option<ItemProperties>>,
+    /// Changed properties that weren't sent to the daemon yet, by input index
+    edited: HashMap<usize, ItemProperties>,
+    requested: HashSet<usize>,
+    /// Inputs whose properties couldn't be retrieved, they aren't requested again until retried
+    failed: HashSet<usize>
+}
+
+impl PropertiesEditor {
+    pub fn loaded(&mut self, input: usize, properties: Option<ItemProperties>) {
+        self.requested.remove(&input);
+        self.loaded.insert(input, properties);
+    }
+
+    pub fn is_dirty(&self, input: usize) -> bool {
+        self.edited.contains_key(&input)
+    }
+
+    /// Properties of the input including unsaved changes, `None` if they weren't retrieved yet
+    pub fn current(&self, input: usize) -> Option<Option<ItemProperties>> {
+        match self.edited.get(&input) {
+            Some(edited) => Some(Some(edited.clone())),
+            None => self.loaded.get(&input).cloned()
+        }
+    }
+
+    /// Asks the daemon for properties of the input, unless they are already retrieved, on the way or failed
+    pub fn request(&mut self, sender: &Sender<UIRequest>, operations: &mut Operations, identifier: &NamespacedDeviceIdentifier, input: usize) {
+        if self.loaded.contains_key(&input) || self.failed.contains(&input) || !self.requested.insert(input) {
+            return;
+        }
+
+        let id = send_ui_message(sender, UIMessage::GetItemProperties {
+            identifier: identifier.clone(),
+            input
+        });
+
+        operations.start(id, PendingOperation::LoadProperties {
+            identifier: identifier.clone(),
+            input
+        });
+    }
+
+    pub fn has_failed(&self, input: usize) -> bool {
+        self.failed.contains(&input)
+    }
+
+    pub fn load_failed(&mut self, input: usize) {
+        self.requested.remove(&input);
+        self.failed.insert(input);
+    }
+
+    /// Brings back changes that daemon didn't accept, they'll be compared against freshly retrieved properties
+    pub fn save_failed(&mut self, input: usize, properties: ItemProperties) {
+        self.loaded.remove(&input);
+        self.edited.insert(input, properties);
+    }
+
+    /// Forgets properties of the input, so they get retrieved again
+    pub fn invalidate(&mut self, input: usize) {
+        self.loaded.remove(&input);
+        self.failed.remove(&input);
+    }
+
+    /// Forgets properties of the input including unsaved changes, for when they get replaced
+    pub fn discard(&mut self, input: usize) {
+        self.edited.remove(&input);
+        self.invalidate(input);
+    }
+
+    /// Forgets everything, including unsaved changes
+    pub fn clear(&mut self) {
+        *self = Default::default();
+    }
+
+    /// Marks changes of the input as saved
+    fn saved(&mut self, input: usize) {
+        if let Some(properties) = self.edited.remove(&input) {
+            self.loaded.insert(input, Some(properties));
+        }
+    }
+}
+
+pub fn properties_ui(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIRequest>) {
     ui.vertical(|ui| {
-        let tab_text = [
-            "Properties".to_string(),
-            "Templates".to_string()
-        ];
+        let tab_list = EditorTab::ALL.map(|tab| tab.tab());
+        let current_tab = EditorTab::ALL.iter()
+            .position(|tab| *tab == state.device_editor.tab)
+            .unwrap_or_default();
 
-        tabs(ui, &tab_text, &tab_text[0], 10.0, state.device_editor.grid_collapsed);
+        if let TabResponse::TabClicked(index) = tabs(ui, &tab_list, current_tab, 10.0, state.device_editor.grid_collapsed) {
+            state.device_editor.tab = EditorTab::ALL[index];
+        }
 
         let next_widget = ui.next_widget_position();
         let available_size = ui.available_size();
@@ -31,13 +128,11 @@ pub fn properties_ui(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage
             ui.vertical(|ui| {
                 ScrollArea::vertical()
                     .show(ui, |ui| {
-                        for i in 0..200 {
-                            if ui.button("aa").clicked() {
-                                state.device_editor.grid_collapsed = !state.device_editor.grid_collapsed;
-                            }
+                        match state.device_editor.tab {
+                            EditorTab::Properties => selection_ui(ui, state, sender),
+                            EditorTab::Templates => templates_ui(ui, state, sender)
                         }
 
-
                         ui.allocate_space(ui.available_size());
                     })
             })
@@ -46,4 +141,191 @@ pub fn properties_ui(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage
 
 
     ui.allocate_space(ui.available_size());
-}
\ No newline at end of file
+}
+
+fn selection_ui(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIRequest>) {
+    let editor = &state.device_editor;
+
+    let Some(grid) = &editor.grid else {
+        return;
+    };
+
+    if editor.selection.is_empty() {
+        ui.label(RichText::new("Select an input to see its properties").weak());
+        return;
+    }
+
+    let selected: Vec<usize> = editor.selection.iter().collect();
+
+    if let [input] = selected[..] {
+        item_properties_ui(ui, state, sender, input);
+        return;
+    }
+
+    ui.heading(format!("{} inputs selected", selected.len()));
+    ui.add_space(5.0);
+
+    for index in selected {
+        let Some(item) = grid.items.get(index) else {
+            continue;
+        };
+
+        let contents = match editor.items.get(index) {
+            Some(ItemPreview::Empty) => "Empty",
+            Some(ItemPreview::NotRenderable) => "Not drawn on the device",
+            Some(ItemPreview::Unavailable) | Some(ItemPreview::Image(_)) => "Drawn on the device",
+            None => "Loading..."
+        };
+
+        let mut title = format!("Input {}", index + 1);
+        if editor.properties.is_dirty(index) {
+            title.push_str(" \u{2022}");
+        }
+
+        ui.label(RichText::new(title).strong());
+        ui.label(format!("{:?} at {}, {}", item.looks.icon, item.looks.x, item.looks.y));
+        ui.label(RichText::new(contents).weak());
+        ui.add_space(5.0);
+    }
+}
+
+fn item_properties_ui(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIRequest>, input: usize) {
+    let editor = &mut state.device_editor;
+    let properties = &mut editor.properties;
+
+    if properties.has_failed(input) {
+        ui.heading(format!("Input {}", input + 1));
+        ui.label(RichText::new("Couldn't retrieve properties of this input").color(ui.style().visuals.error_fg_color));
+
+        if ui.button("Retry").clicked() {
+            properties.invalidate(input);
+        }
+        return;
+    }
+
+    let Some(loaded) = properties.loaded.get(&input) else {
+        ui.spinner();
+        properties.request(sender, &mut state.operations, &editor.device, input);
+        return;
+    };
+
+    let Some(loaded) = loaded.clone() else {
+        ui.heading(format!("Input {}", input + 1));
+        ui.label(RichText::new("Nothing is assigned to this input").weak());
+        return;
+    };
+
+    let saving = is_saving(&state.operations, &editor.device, input);
+
+    ui.horizontal(|ui| {
+        ui.heading(format!("Input {}", input + 1));
+
+        if saving {
+            ui.spinner();
+            ui.label(RichText::new("Saving...").weak());
+        } else if properties.is_dirty(input) {
+            ui.label(RichText::new("\u{2022} Unsaved changes").color(ui.style().visuals.warn_fg_color));
+        }
+    });
+    ui.add_space(5.0);
+
+    let mut edited = properties.edited.get(&input).cloned().unwrap_or_else(|| loaded.clone());
+
+    ui.checkbox(&mut edited.renderable, "Drawn on the device");
+    ui.add_space(5.0);
+
+    ui.label(RichText::new("Action").strong());
+    match &edited.action {
+        Some(action) => ui.label(action.to_string()),
+        None => ui.label(RichText::new("No action").weak())
+    };
+    ui.add_space(5.0);
+
+    if !edited.parameters.is_empty() {
+        ui.label(RichText::new("Parameters").strong());
+
+        for (name, value) in edited.parameters.iter_mut() {
+            ui.horizontal(|ui| {
+                ui.label(name);
+                parameter_ui(ui, value);
+            });
+        }
+        ui.add_space(5.0);
+    }
+
+    if edited == loaded {
+        properties.edited.remove(&input);
+    } else {
+        properties.edited.insert(input, edited.clone());
+    }
+
+    let dirty = properties.is_dirty(input);
+
+    ui.horizontal(|ui| {
+        if ui.add_enabled(dirty, Button::new("Save")).clicked() {
+            let id = send_ui_message(sender, UIMessage::SetItemProperties {
+                identifier: editor.device.clone(),
+                input,
+                properties: edited.clone()
+            });
+
+            state.operations.start(id, PendingOperation::SaveProperties {
+                identifier: editor.device.clone(),
+                input,
+                properties: edited
+            });
+
+            // Optimistically, changes come back if saving fails
+            properties.saved(input);
+        }
+
+        if ui.add_enabled(dirty, Button::new("Revert")).clicked() {
+            properties.edited.remove(&input);
+        }
+    });
+}
+
+/// If properties of the input are being saved to the daemon
+pub fn is_saving(operations: &Operations, identifier: &NamespacedDeviceIdentifier, input: usize) -> bool {
+    operations.any(|operation| matches!(
+        operation,
+        PendingOperation::SaveProperties { identifier: i, input: n, .. } if i == identifier && *n == input
+    ))
+}
+
+fn parameter_ui(ui: &mut Ui, value: &mut Value) {
+    match value {
+        Value::Bool(value) => {
+            ui.checkbox(value, "");
+        }
+
+        Value::Number(number) => {
+            // Editing with the number's own kind, so big integers don't lose precision through f64.
+            // Non-negative integers are edited as signed when they fit, so they can go below zero
+            if let Some(mut integer) = number.as_i64() {
+                if ui.add(DragValue::new(&mut integer)).changed() {
+                    *number = Number::from(integer);
+                }
+            } else if let Some(mut unsigned) = number.as_u64() {
+                if ui.add(DragValue::new(&mut unsigned)).changed() {
+                    *number = Number::from(unsigned);
+                }
+            } else if let Some(mut float) = number.as_f64() {
+                if ui.add(DragValue::new(&mut float)).changed() {
+                    if let Some(changed) = Number::from_f64(float) {
+                        *number = changed;
+                    }
+                }
+            }
+        }
+
+        Value::String(text) => {
+            ui.add(TextEdit::singleline(text));
+        }
+
+        // Nested values can't be edited yet
+        value => {
+            ui.label(RichText::new(value.to_string()).monospace().color(Color32::GRAY));
+        }
+    }
+}
@@ -0,0 +1,150 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::BTreeSet;
+use egui::Modifiers;
+
+/// Selected inputs of the input grid, by input index
+#[derive(Default, Debug)]
+pub struct Selection {
+    selected: BTreeSet<usize>,
+    /// Where shift selection starts from
+    anchor: Option<usize>,
+    /// Last input that was selected, keyboard navigation moves from it
+    focus: Option<usize>
+}
+
+impl Selection {
+    pub fn contains(&self, index: usize) -> bool {
+        self.selected.contains(&index)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.selected.is_empty()
+    }
+
+    pub fn focus(&self) -> Option<usize> {
+        self.focus
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.selected.iter().copied()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Default::default();
+    }
+
+    /// Selects the input like a click would, shift selects a range and ctrl toggles
+    pub fn click(&mut self, index: usize, modifiers: Modifiers) {
+        if modifiers.shift {
+            self.extend_to(index, modifiers.command);
+        } else if modifiers.command {
+            if !self.selected.remove(&index) {
+                self.selected.insert(index);
+            }
+
+            self.anchor = Some(index);
+            self.focus = Some(index);
+        } else {
+            self.select_only(index);
+        }
+    }
+
+    pub fn select_only(&mut self, index: usize) {
+        self.selected = BTreeSet::from([index]);
+        self.anchor = Some(index);
+        self.focus = Some(index);
+    }
+
+    /// Selects everything between the anchor and the input, keeping rest of the selection if `keep` is set
+    pub fn extend_to(&mut self, index: usize, keep: bool) {
+        let anchor = self.anchor.unwrap_or(index);
+
+        if !keep {
+            self.selected.clear();
+        }
+
+        self.selected.extend(anchor.min(index)..=anchor.max(index));
+        self.anchor = Some(anchor);
+        self.focus = Some(index);
+    }
+
+    /// Adds the input to the selection and moves focus to it
+    pub fn add(&mut self, index: usize) {
+        self.selected.insert(index);
+        self.focus = Some(index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Modifiers;
+    use super::Selection;
+
+    fn selected(selection: &Selection) -> Vec<usize> {
+        selection.iter().collect()
+    }
+
+    #[test]
+    fn click_selects_only_the_input() {
+        let mut selection = Selection::default();
+
+        selection.click(2, Modifiers::NONE);
+        selection.click(5, Modifiers::NONE);
+
+        assert_eq!(selected(&selection), vec![5]);
+        assert_eq!(selection.focus(), Some(5));
+    }
+
+    #[test]
+    fn command_click_toggles() {
+        let mut selection = Selection::default();
+
+        selection.click(1, Modifiers::NONE);
+        selection.click(3, Modifiers::COMMAND);
+        assert_eq!(selected(&selection), vec![1, 3]);
+
+        selection.click(1, Modifiers::COMMAND);
+        assert_eq!(selected(&selection), vec![3]);
+        assert_eq!(selection.focus(), Some(1));
+    }
+
+    #[test]
+    fn shift_click_selects_range_from_anchor() {
+        let mut selection = Selection::default();
+
+        selection.click(4, Modifiers::NONE);
+        selection.click(1, Modifiers::SHIFT);
+        assert_eq!(selected(&selection), vec![1, 2, 3, 4]);
+
+        // Anchor stays, so the range is replaced rather than grown
+        selection.click(6, Modifiers::SHIFT);
+        assert_eq!(selected(&selection), vec![4, 5, 6]);
+        assert_eq!(selection.focus(), Some(6));
+    }
+
+    #[test]
+    fn extend_to_can_keep_selection() {
+        let mut selection = Selection::default();
+
+        selection.click(0, Modifiers::NONE);
+        selection.click(8, Modifiers::COMMAND);
+        selection.extend_to(6, true);
+
+        assert_eq!(selected(&selection), vec![0, 6, 7, 8]);
+    }
+
+    #[test]
+    fn extend_to_without_anchor_selects_the_input() {
+        let mut selection = Selection::default();
+
+        selection.extend_to(3, false);
+
+        assert_eq!(selected(&selection), vec![3]);
+        assert_eq!(selection.focus(), Some(3));
+    }
+}
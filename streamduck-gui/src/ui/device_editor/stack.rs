@@ -1,19 +1,26 @@
-﻿use egui::{Button, Color32, Frame, pos2, Rect, RichText, ScrollArea, Stroke, Ui, vec2};
+﻿use egui::{Button, Color32, Frame, pos2, Rect, RichText, ScrollArea, Ui, vec2};
 use tokio::sync::mpsc::Sender;
-use crate::ui::{UIMessage, UIState};
+use crate::ui::{UIMessage, UIRequest, UIState};
+use crate::ui::operations::PendingOperation;
 use crate::ui::util::send_ui_message;
 
-pub fn stack_line(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage>) {
+pub fn stack_line(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIRequest>) {
     let button_width = ui.available_height();
     let margin = 10f32;
 
     let all_width = ui.available_width();
     let all_height = ui.available_height();
 
-    let stack_width = all_width - button_width - margin;
+    let buttons_width = button_width * 2.0 + margin;
+    let stack_width = all_width - buttons_width - margin;
 
     let next_pos = ui.available_rect_before_wrap().min;
 
+    // Stack shown is about to change, so it can't be changed further until then
+    let device = state.device_editor.device.clone();
+    let pending = state.operations.any(|operation| operation == &PendingOperation::ChangeStack(device.clone()));
+    let mut message = None;
+
     let left_rect = Rect::from_min_size(
         next_pos,
         vec2(stack_width, all_height),
@@ -24,7 +31,7 @@ pub fn stack_line(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage>)
             next_pos.x + stack_width + margin,
             next_pos.y,
         ),
-        vec2(button_width, all_height),
+        vec2(buttons_width, all_height),
     );
 
     ui.allocate_ui_at_rect(left_rect, |ui| {
@@ -36,13 +43,27 @@ pub fn stack_line(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage>)
                 ScrollArea::horizontal()
                     .show(ui, |ui| {
                         ui.add_space(10.0);
+                        let depth = state.device_editor.depth();
+
                         for (index, stack_item) in state.device_editor.stack.iter().enumerate() {
                             if index != 0 {
                                 ui.add_space(6.0);
                                 ui.label(RichText::new("\u{eab6}").line_height(Some(16.0)));
                             }
 
-                            ui.label(RichText::new(stack_item).line_height(Some(19.0)));
+                            let text = RichText::new(stack_item).line_height(Some(19.0));
+
+                            // Breadcrumbs pop back to the screen
+                            if index == depth {
+                                ui.label(text.strong());
+                            } else if ui.add_enabled(!pending, Button::new(text).frame(false))
+                                .on_hover_text("Go back to this screen")
+                                .clicked() {
+                                message = Some(UIMessage::PopToDepth {
+                                    identifier: device.clone(),
+                                    depth: index
+                                });
+                            }
                         }
                         ui.add_space(10.0);
                     });
@@ -50,15 +71,40 @@ pub fn stack_line(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage>)
     });
 
     ui.allocate_ui_at_rect(right_rect, |ui| {
-        if ui.add(
-                Button::new(
-                    RichText::new("\u{f148}")
-                        .line_height(Some(20f32))
-                        .size(16f32)
-                ).min_size(ui.available_size())
-                    .rounding(10f32)
-            ).clicked() {
-            send_ui_message(sender, UIMessage::PopScreen(state.device_editor.device.clone()));
-        }
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = margin;
+
+            if ui.add_enabled(
+                    !pending,
+                    Button::new(
+                        RichText::new("\u{f067}")
+                            .line_height(Some(20f32))
+                            .size(16f32)
+                    ).min_size(vec2(button_width, all_height))
+                        .rounding(10f32)
+                ).on_hover_text("Push new screen").clicked() {
+                message = Some(UIMessage::PushScreen(device.clone()));
+            }
+
+            // Root screen can't be popped
+            let can_pop = state.device_editor.depth() > 0;
+
+            if ui.add_enabled(
+                    !pending && can_pop,
+                    Button::new(
+                        RichText::new("\u{f148}")
+                            .line_height(Some(20f32))
+                            .size(16f32)
+                    ).min_size(vec2(button_width, all_height))
+                        .rounding(10f32)
+                ).on_hover_text("Pop screen").clicked() {
+                message = Some(UIMessage::PopScreen(device.clone()));
+            }
+        });
     });
+
+    if let Some(message) = message {
+        let id = send_ui_message(sender, message);
+        state.operations.start(id, PendingOperation::ChangeStack(device));
+    }
 }
\ No newline at end of file
@@ -5,20 +5,48 @@
  */
 
 use eframe::emath::pos2;
-use egui::{Color32, FontFamily, FontId, Id, Rect, Rounding, Sense, Stroke, Ui, vec2};
+use egui::{Color32, FontFamily, FontId, Id, Key, Modifiers, Rect, Rounding, ScrollArea, Sense, Stroke, Ui, vec2};
+use egui::scroll_area::ScrollBarVisibility;
 use interpolation::Ease;
 use crate::ui::util::lerp_color;
 
-pub enum TabResponse<'a> {
-    TabClicked(&'a str),
+pub struct Tab {
+    pub text: String,
+    pub icon: Option<String>
+}
+
+impl Tab {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            icon: None
+        }
+    }
+
+    pub fn with_icon(mut self, icon: &str) -> Self {
+        self.icon = Some(icon.to_string());
+        self
+    }
+
+    fn label(&self) -> String {
+        match &self.icon {
+            Some(icon) => format!("{icon}  {}", self.text),
+            None => self.text.clone()
+        }
+    }
+}
+
+pub enum TabResponse {
+    /// Tab was clicked or switched to with Ctrl+Tab
+    TabClicked(usize),
     Nothing
 }
 
-pub fn tabs<'a>(ui: &mut Ui, tabs: &'a [String], current_tab: &'a String, bottom_margin: f32, collapsed: bool) -> TabResponse<'a> {
+pub fn tabs(ui: &mut Ui, tabs: &[Tab], current_tab: usize, bottom_margin: f32, collapsed: bool) -> TabResponse {
     let available_space = ui.available_size();
 
     let animated_collapse = ui.ctx().animate_value_with_time(
-        Id::new(tabs).with("collapse"),
+        Id::new("editor_tabs").with("collapse"),
         if collapsed { 0.0 } else { 1.0 },
         0.2f32
     ).cubic_in_out();
@@ -32,71 +60,100 @@ pub fn tabs<'a>(ui: &mut Ui, tabs: &'a [String], current_tab: &'a String, bottom
     
     let extra_fill_height = 80f32;
 
-    let (id, rect) = ui.allocate_space(vec2(element_width, element_height));
+    let (_, rect) = ui.allocate_space(vec2(element_width, element_height));
     
     ui.painter().rect(rect, 0.0, ui.style().visuals.panel_fill, Stroke::NONE);
 
-    let mut current_offset = 0.0f32;
-    for tab in tabs {
-        let galley = ui.painter().layout(
-            tab.clone(),
-            FontId::new(20.0, FontFamily::Proportional),
-            Color32::PLACEHOLDER,
-            10000.0
-        );
-
-        let horizontal_margin = 15.0f32;
-        let width = galley.rect.width() + (horizontal_margin * 2.0);
-
-        let size = vec2(width, tab_height);
-        let tab_pos = pos2(
-            rect.min.x + current_offset,
-            rect.min.y
-        );
-        let tab_rect = Rect::from_min_size(tab_pos, size);
-
-        current_offset += width;
-
-        let response = ui.allocate_rect(tab_rect, Sense::click());
-        let tab_style = ui.style().interact(&response);
-
-        let tab_color = if tab == current_tab {
-            Color32::from_rgb(40, 40, 40)
-        } else {
-            Color32::from_rgb(30, 30, 30)
-        };
-
-        ui.painter().rect(
-            tab_rect,
-            Rounding {
-                nw: 10.0,
-                ne: 10.0,
-                sw: 0.0,
-                se: 0.0,
-            },
-            tab_color,
-            Stroke::NONE
-        );
-
-        ui.painter().rect(
-            tab_rect,
-            10.0,
-            if response.hovered() {
-                tab_style.bg_fill
-            } else {
-                tab_color
-            },
-            Stroke::NONE
-        );
-
-        let text_pos = pos2(
-            tab_rect.min.x + horizontal_margin,
-            tab_rect.min.y + (theoretical_tab_height / 2.0 - galley.rect.height() / 2.0)
-        );
-
-        ui.painter().galley(text_pos, galley, lerp_color(&Color32::TRANSPARENT, &tab_style.fg_stroke.color, animated_collapse));
+    let mut tab_response = TabResponse::Nothing;
+
+    // Keyboard switching
+    if !almost_collapsed && !tabs.is_empty() {
+        // Shift variant goes first, as the plain shortcut matches it too
+        let (previous, next) = ui.input_mut(|input| {
+            let previous = input.consume_key(Modifiers::COMMAND | Modifiers::SHIFT, Key::Tab);
+            (previous, !previous && input.consume_key(Modifiers::COMMAND, Key::Tab))
+        });
+
+        if previous {
+            tab_response = TabResponse::TabClicked((current_tab + tabs.len() - 1) % tabs.len());
+        } else if next {
+            tab_response = TabResponse::TabClicked((current_tab + 1) % tabs.len());
+        }
     }
 
+    let strip_rect = Rect::from_min_size(rect.min, vec2(element_width, tab_height));
+
+    ui.allocate_ui_at_rect(strip_rect, |ui| {
+        ScrollArea::horizontal()
+            .id_source("editor_tabs")
+            .scroll_bar_visibility(ScrollBarVisibility::AlwaysHidden)
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 0.0;
+
+                    for (index, tab) in tabs.iter().enumerate() {
+                        let galley = ui.painter().layout(
+                            tab.label(),
+                            FontId::new(20.0, FontFamily::Proportional),
+                            Color32::PLACEHOLDER,
+                            10000.0
+                        );
+
+                        let horizontal_margin = 15.0f32;
+                        let width = galley.rect.width() + (horizontal_margin * 2.0);
+
+                        let (tab_rect, response) = ui.allocate_exact_size(vec2(width, tab_height), Sense::click());
+                        let tab_style = ui.style().interact(&response);
+
+                        if response.clicked() {
+                            tab_response = TabResponse::TabClicked(index);
+                        }
+
+                        // Keeping switched to tab in view
+                        if matches!(tab_response, TabResponse::TabClicked(clicked) if clicked == index) {
+                            response.scroll_to_me(None);
+                        }
+
+                        let tab_color = if index == current_tab {
+                            Color32::from_rgb(40, 40, 40)
+                        } else {
+                            Color32::from_rgb(30, 30, 30)
+                        };
+
+                        ui.painter().rect(
+                            tab_rect,
+                            Rounding {
+                                nw: 10.0,
+                                ne: 10.0,
+                                sw: 0.0,
+                                se: 0.0,
+                            },
+                            tab_color,
+                            Stroke::NONE
+                        );
+
+                        ui.painter().rect(
+                            tab_rect,
+                            10.0,
+                            if response.hovered() {
+                                tab_style.bg_fill
+                            } else {
+                                tab_color
+                            },
+                            Stroke::NONE
+                        );
+
+                        let text_pos = pos2(
+                            tab_rect.min.x + horizontal_margin,
+                            tab_rect.min.y + (theoretical_tab_height / 2.0 - galley.rect.height() / 2.0)
+                        );
+
+                        ui.painter().galley(text_pos, galley, lerp_color(&Color32::TRANSPARENT, &tab_style.fg_stroke.color, animated_collapse));
+                    }
+                });
+            });
+    });
+
     // Bottom adapter
     let bottom_rect = Rect::from_min_size(
         pos2(rect.min.x, rect.max.y - bottom_margin),
@@ -114,6 +171,6 @@ pub fn tabs<'a>(ui: &mut Ui, tabs: &'a [String], current_tab: &'a String, bottom
         Stroke::NONE
     );
 
-    TabResponse::Nothing
+    tab_response
 }
 
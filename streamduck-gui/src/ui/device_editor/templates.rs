@@ -0,0 +1,188 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use egui::{Button, Color32, Context, Frame, Id, Image, RichText, Sense, TextEdit, TextureHandle, TextureOptions, Ui, vec2};
+use tokio::sync::mpsc::Sender;
+use streamduck_rust_client::api::PartialScreenItem;
+use crate::templates::{StoredTemplate, Template};
+use crate::ui::{UIRequest, UIState};
+use crate::ui::device_editor::previews::decode_preview;
+use crate::ui::notifications::{Notification, Notifications};
+
+const PREVIEW_SIZE: f32 = 40.0;
+
+/// Template that is being dragged onto an input, by path of its file
+pub struct TemplateDrag(pub PathBuf);
+
+#[derive(Default)]
+pub struct TemplatesTab {
+    pub search: String,
+    pub new_name: String,
+    previews: HashMap<PathBuf, Option<TextureHandle>>
+}
+
+impl TemplatesTab {
+    /// Preview is only decoded once, so failure is reported once too
+    fn preview(&mut self, ctx: &Context, notifications: &mut Notifications, stored: &StoredTemplate) -> Option<TextureHandle> {
+        self.previews.entry(stored.path.clone())
+            .or_insert_with(|| {
+                let item = PartialScreenItem {
+                    renderable: true,
+                    base64jpg: stored.template.preview.clone()
+                };
+
+                item.base64jpg.as_ref()?;
+                let image = decode_preview(&item)
+                    .map_err(|error| notifications.push(Notification::error("Failed to decode template preview")
+                        .with_details(format!("{}: {error}", stored.template.name))))
+                    .ok()?;
+
+                Some(ctx.load_texture(format!("template_{}", stored.path.display()), image, TextureOptions::LINEAR))
+            })
+            .clone()
+    }
+}
+
+pub fn templates_ui(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIRequest>) {
+    save_template_ui(ui, state, sender);
+    ui.add_space(10.0);
+
+    let tab = &mut state.device_editor.templates;
+
+    ui.add(TextEdit::singleline(&mut tab.search)
+        .hint_text("Search templates")
+        .desired_width(f32::INFINITY));
+    ui.add_space(5.0);
+
+    if state.templates.templates().is_empty() {
+        ui.label(RichText::new("No templates yet, select an input and save it as one").weak());
+        return;
+    }
+
+    let mut removed = None;
+    let mut any_found = false;
+
+    for stored in state.templates.search(&tab.search) {
+        any_found = true;
+
+        ui.horizontal(|ui| {
+            let preview = tab.preview(ui.ctx(), &mut state.notifications, stored);
+
+            ui.dnd_drag_source(Id::new("template").with(&stored.path), TemplateDrag(stored.path.clone()), |ui| {
+                Frame::default()
+                    .fill(Color32::from_rgb(30, 30, 30))
+                    .rounding(8.0)
+                    .inner_margin(5.0)
+                    .show(ui, |ui| {
+                        ui.set_width(ui.available_width() - 40.0);
+
+                        ui.horizontal(|ui| {
+                            match preview {
+                                Some(texture) => {
+                                    ui.add(Image::new(&texture)
+                                        .fit_to_exact_size(vec2(PREVIEW_SIZE, PREVIEW_SIZE))
+                                        .rounding(5.0));
+                                }
+                                None => {
+                                    let (rect, _) = ui.allocate_exact_size(vec2(PREVIEW_SIZE, PREVIEW_SIZE), Sense::hover());
+                                    ui.painter().rect_filled(rect, 5.0, ui.style().visuals.widgets.inactive.bg_fill);
+                                }
+                            }
+
+                            ui.vertical(|ui| {
+                                ui.label(RichText::new(&stored.template.name).strong());
+
+                                match &stored.template.properties.action {
+                                    Some(action) => ui.label(RichText::new(action.to_string()).weak()),
+                                    None => ui.label(RichText::new("No action").weak())
+                                };
+                            });
+                        });
+                    });
+            }).response.on_hover_text("Drag onto an input to apply");
+
+            if ui.add(Button::new("\u{f1f8}").min_size(vec2(30.0, 30.0))).on_hover_text("Delete template").clicked() {
+                removed = Some(stored.path.clone());
+            }
+        });
+    }
+
+    if !any_found {
+        ui.label(RichText::new("No templates match the search").weak());
+    }
+
+    if let Some(path) = removed {
+        if let Err(error) = state.templates.remove(&path) {
+            state.notifications.push(Notification::error("Failed to delete template").with_details(error));
+        }
+
+        state.device_editor.templates.previews.clear();
+    }
+}
+
+fn save_template_ui(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIRequest>) {
+    let editor = &mut state.device_editor;
+    let selected: Vec<usize> = editor.selection.iter().collect();
+
+    let [input] = selected[..] else {
+        ui.label(RichText::new("Select a single input to save it as a template").weak());
+        return;
+    };
+
+    if editor.properties.has_failed(input) {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Couldn't retrieve properties of the selected input").color(ui.style().visuals.error_fg_color));
+
+            if ui.button("Retry").clicked() {
+                editor.properties.invalidate(input);
+            }
+        });
+        return;
+    }
+
+    let Some(properties) = editor.properties.current(input) else {
+        ui.spinner();
+        editor.properties.request(sender, &mut state.operations, &editor.device, input);
+        return;
+    };
+
+    let Some(properties) = properties else {
+        ui.label(RichText::new("Nothing is assigned to the selected input").weak());
+        return;
+    };
+
+    let tab = &mut editor.templates;
+
+    ui.horizontal(|ui| {
+        ui.add(TextEdit::singleline(&mut tab.new_name)
+            .hint_text("Template name")
+            .desired_width(ui.available_width() - 130.0));
+
+        let valid_name = !tab.new_name.trim().is_empty();
+
+        if ui.add_enabled(valid_name, Button::new("Save as template")).clicked() {
+            let preview = editor.screen_items.get(input)
+                .and_then(|item| item.as_ref())
+                .and_then(|item| item.base64jpg.clone());
+
+            let template = Template {
+                name: tab.new_name.trim().to_string(),
+                properties,
+                preview
+            };
+
+            match state.templates.save(template) {
+                Ok(()) => {
+                    tab.new_name.clear();
+                    tab.previews.clear();
+                }
+                Err(error) => state.notifications.push(Notification::error("Failed to save template").with_details(error))
+            }
+        }
+    });
+}
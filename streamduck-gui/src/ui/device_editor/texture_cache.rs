@@ -0,0 +1,124 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use egui::{ColorImage, Context, TextureHandle, TextureOptions};
+use streamduck_rust_client::base::NamespacedDeviceIdentifier;
+
+const DEFAULT_CAPACITY: usize = 256;
+
+#[derive(Clone, Debug, Hash, Eq, PartialEq)]
+pub struct PreviewKey {
+    pub device: NamespacedDeviceIdentifier,
+    pub depth: usize,
+    pub index: usize
+}
+
+struct CachedTexture {
+    hash: u64,
+    texture: TextureHandle,
+    last_used: u64
+}
+
+/// Uploaded previews, so keys that didn't change aren't decoded and uploaded again
+pub struct TextureCache {
+    capacity: usize,
+    entries: HashMap<PreviewKey, CachedTexture>,
+    /// Content hashes of previews that couldn't be decoded, kept through invalidation since same content fails again
+    failed: HashSet<u64>,
+    tick: u64
+}
+
+impl Default for TextureCache {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            entries: Default::default(),
+            failed: Default::default(),
+            tick: 0
+        }
+    }
+}
+
+impl TextureCache {
+    /// Returns cached texture if the preview didn't change, otherwise decodes it with `load` and uploads it
+    ///
+    /// Failed decodes are remembered too, `load` isn't called for the same content again
+    pub fn get_or_load(&mut self, ctx: &Context, key: PreviewKey, encoded: &str, load: impl FnOnce() -> Option<ColorImage>) -> Option<TextureHandle> {
+        self.tick += 1;
+        let hash = content_hash(encoded);
+
+        if let Some(entry) = self.entries.get_mut(&key).filter(|entry| entry.hash == hash) {
+            entry.last_used = self.tick;
+            return Some(entry.texture.clone());
+        }
+
+        if self.failed.contains(&hash) {
+            self.entries.remove(&key);
+            return None;
+        }
+
+        let Some(image) = load() else {
+            self.entries.remove(&key);
+
+            if self.failed.len() >= self.capacity {
+                self.failed.clear();
+            }
+            self.failed.insert(hash);
+
+            return None;
+        };
+
+        let name = format!("item_preview_{}_{}_{}", key.device, key.depth, key.index);
+        let texture = ctx.load_texture(name, image, TextureOptions::LINEAR);
+
+        self.entries.insert(key, CachedTexture {
+            hash,
+            texture: texture.clone(),
+            last_used: self.tick
+        });
+        self.evict();
+
+        Some(texture)
+    }
+
+    /// Removes a single preview
+    pub fn invalidate(&mut self, key: &PreviewKey) {
+        self.entries.remove(key);
+    }
+
+    /// Removes all previews of the device's screen at the depth
+    pub fn invalidate_screen(&mut self, device: &NamespacedDeviceIdentifier, depth: usize) {
+        self.entries.retain(|key, _| &key.device != device || key.depth != depth);
+    }
+
+    /// Removes previews of all device's screens at the depth or deeper, for when screens get popped or replaced
+    pub fn invalidate_from_depth(&mut self, device: &NamespacedDeviceIdentifier, depth: usize) {
+        self.entries.retain(|key, _| &key.device != device || key.depth < depth);
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            let oldest = self.entries.iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+
+            let Some(oldest) = oldest else {
+                break;
+            };
+
+            self.entries.remove(&oldest);
+        }
+    }
+}
+
+fn content_hash(encoded: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    encoded.hash(&mut hasher);
+    hasher.finish()
+}
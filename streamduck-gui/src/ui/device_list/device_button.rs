@@ -4,13 +4,19 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
-use egui::{Color32, FontFamily, FontId, Id, pos2, Rect, Sense, Stroke, Ui, vec2};
+use egui::{Color32, FontFamily, FontId, Id, pos2, Rect, Sense, Spinner, Stroke, Ui, vec2};
 use egui::epaint::TextShape;
 use streamduck_rust_client::base::NamespacedDeviceIdentifier;
 use interpolation::{Lerp, Ease};
 use crate::ui::util::{interact_lerped_selectable, lerp_color};
 
-pub fn device_button(ui: &mut Ui, device: &NamespacedDeviceIdentifier, connected: bool, autoconnect: bool) -> DeviceButtonResponse {
+/// Operations on the device that are in progress
+pub struct DevicePending {
+    pub connecting: bool,
+    pub autoconnect: bool
+}
+
+pub fn device_button(ui: &mut Ui, device: &NamespacedDeviceIdentifier, connected: bool, autoconnect: bool, pending: DevicePending) -> DeviceButtonResponse {
     let max_rect = ui.max_rect();
 
     let animation_time = 0.2f32;
@@ -34,7 +40,8 @@ pub fn device_button(ui: &mut Ui, device: &NamespacedDeviceIdentifier, connected
     );
 
     let main_button_response = ui.allocate_rect(main_button_rect, Sense::click());
-    let checkbox_response = ui.allocate_rect(checkbox_rect, Sense::click());
+    // Toggling again before the daemon answers would let a rollback undo the newer toggle
+    let checkbox_response = ui.allocate_rect(checkbox_rect, if pending.autoconnect { Sense::hover() } else { Sense::click() });
 
     // Don't draw if not visible
     if !ui.is_rect_visible(element_rect) {
@@ -79,6 +86,12 @@ pub fn device_button(ui: &mut Ui, device: &NamespacedDeviceIdentifier, connected
         selection,
         Stroke::NONE);
 
+    if pending.autoconnect {
+        Spinner::new()
+            .color(ui.style().visuals.widgets.inactive.fg_stroke.color)
+            .paint_at(ui, checkbox_bg_rect.shrink(10.0));
+    }
+
     // Connected tip
     let animated_connected_id = id.with("connected_tip");
     let animated_connected = ui.ctx().animate_value_with_time(
@@ -100,7 +113,18 @@ pub fn device_button(ui: &mut Ui, device: &NamespacedDeviceIdentifier, connected
         1000.0
     );
 
-    let connected_text_gap = (main_button_rect.height() / 2.0 - connected_galley.rect.height() / 2.0);
+    let connected_text_gap = main_button_rect.height() / 2.0 - connected_galley.rect.height() / 2.0;
+
+    if pending.connecting {
+        let size = main_button_rect.height() / 2.0;
+
+        Spinner::new()
+            .color(main_button_style.fg_stroke.color)
+            .paint_at(ui, Rect::from_center_size(
+                pos2(main_button_rect.max.x - connected_text_gap - size / 2.0, main_button_rect.center().y),
+                vec2(size, size)
+            ));
+    }
 
     ui.painter().galley(
         pos2(
@@ -4,14 +4,12 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 
-use std::collections::HashSet;
-use egui::{Color32, Frame, RichText, ScrollArea, Ui, vec2};
-use egui::epaint::Shadow;
+use egui::{Button, Color32, Frame, RichText, ScrollArea, Ui, vec2};
 use tokio::sync::mpsc::Sender;
 use streamduck_rust_client::api::Device;
-use streamduck_rust_client::base::{DeviceIdentifier, NamespacedDeviceIdentifier, NamespacedName};
-use crate::ui::device_list::device_button::{device_button, DeviceButtonResponse};
-use crate::ui::{Pages, UIMessage, UIState};
+use crate::ui::device_list::device_button::{device_button, DeviceButtonResponse, DevicePending};
+use crate::ui::{UIMessage, UIRequest, UIState};
+use crate::ui::operations::PendingOperation;
 use crate::ui::util::send_ui_message;
 
 mod device_button;
@@ -21,13 +19,19 @@ pub struct DeviceList {
     pub devices: Vec<Device>,
 }
 
-pub fn device_list(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage>) {
+pub fn device_list(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIRequest>) {
     Frame::default()
         .fill(Color32::from_rgb(40, 40, 40))
         .rounding(10.0)
         .inner_margin(10.0)
         .show(ui, |ui| {
-            ui.vertical_centered(|ui| {
+            ui.horizontal(|ui| {
+                if ui.add(Button::new(RichText::new("\u{f013}").size(30.0).line_height(Some(32.0)))
+                    .min_size(vec2(60.0, 50.0))
+                    .rounding(8.0)).on_hover_text("Connection settings").clicked() {
+                    state.open_settings();
+                }
+
                 ui.heading(RichText::new("Select Device")
                     .size(36.0));
             });
@@ -51,7 +55,18 @@ pub fn device_list(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage>)
                                 for (index, response) in
                                     state.device_list.devices.iter().enumerate()
                                         .map(|(index, device)| {
-                                            (index, device_button(ui, &device.identifier, device.connected, device.autoconnect))
+                                            let pending = DevicePending {
+                                                connecting: state.operations.any(|operation| matches!(
+                                                    operation,
+                                                    PendingOperation::ConnectDevice(identifier) if identifier == &device.identifier
+                                                )),
+                                                autoconnect: state.operations.any(|operation| matches!(
+                                                    operation,
+                                                    PendingOperation::Autoconnect { identifier, .. } if identifier == &device.identifier
+                                                ))
+                                            };
+
+                                            (index, device_button(ui, &device.identifier, device.connected, device.autoconnect, pending))
                                         }).collect::<Vec<_>>() {
                                     match response {
                                         DeviceButtonResponse::Open => {
@@ -64,6 +79,7 @@ pub fn device_list(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage>)
                                         DeviceButtonResponse::ToggleAutoconnect => {
                                             let device = &mut state.device_list.devices[index];
 
+                                            // Optimistically, it's flipped back if daemon refuses
                                             device.autoconnect = !device.autoconnect;
 
                                             let message = UIMessage::SetDeviceAutoconnect {
@@ -71,7 +87,11 @@ pub fn device_list(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIMessage>)
                                                 autoconnect: device.autoconnect
                                             };
 
-                                            send_ui_message(sender, message);
+                                            let id = send_ui_message(sender, message);
+                                            state.operations.start(id, PendingOperation::Autoconnect {
+                                                identifier: device.identifier.clone(),
+                                                previous: !device.autoconnect
+                                            });
                                         }
                                         DeviceButtonResponse::Nothing => {}
                                     }
@@ -0,0 +1,54 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use egui::{Color32, Frame, RichText, Ui};
+use streamduck_rust_client::version::{ConnectionInfo, MAX_SUPPORTED_SOCKET_VERSION, MIN_SUPPORTED_SOCKET_VERSION};
+
+pub fn incompatible_screen(ui: &mut Ui, info: &ConnectionInfo) {
+    Frame::default()
+        .fill(Color32::from_rgb(40, 40, 40))
+        .rounding(10.0)
+        .inner_margin(10.0)
+        .show(ui, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.heading(RichText::new("Incompatible Daemon")
+                    .size(36.0));
+            });
+        });
+
+    ui.add_space(2.0);
+
+    Frame::default()
+        .fill(Color32::from_rgb(40, 40, 40))
+        .rounding(10.0)
+        .inner_margin(10.0)
+        .show(ui, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(20.0);
+                ui.label(RichText::new("\u{f0026}").size(64.0));
+                ui.add_space(10.0);
+
+                ui.label(RichText::new(format!(
+                    "Streamduck daemon uses socket version {}",
+                    info.socket_version.as_deref().unwrap_or("unknown")
+                )).size(18.0));
+
+                let supported = if MIN_SUPPORTED_SOCKET_VERSION == MAX_SUPPORTED_SOCKET_VERSION {
+                    MIN_SUPPORTED_SOCKET_VERSION.to_string()
+                } else {
+                    format!("{} to {}", MIN_SUPPORTED_SOCKET_VERSION, MAX_SUPPORTED_SOCKET_VERSION)
+                };
+
+                ui.label(RichText::new(format!("This version of the GUI only supports socket version {supported}"))
+                    .size(18.0));
+
+                ui.add_space(10.0);
+                ui.label("Update either the GUI or the daemon so they match, the GUI will reconnect on its own");
+            });
+
+            ui.allocate_space(ui.available_size());
+        });
+}
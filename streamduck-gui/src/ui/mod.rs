@@ -7,25 +7,45 @@
 mod device_list;
 mod util;
 mod device_editor;
+mod incompatible;
+pub mod connection;
+mod settings;
+mod persistence;
+pub mod notifications;
+pub mod operations;
 
-use std::sync::{Arc, Condvar};
 use std::thread;
-use eframe::{App, CreationContext, NativeOptions, run_native};
-use egui::{CentralPanel, Context, FontData, FontDefinitions, FontFamily, FontId, RichText, TextStyle, Frame, Color32, vec2};
+use eframe::{App, CreationContext, NativeOptions, run_native, Storage};
+use egui::{CentralPanel, Context, FontData, FontDefinitions, FontFamily, FontId, TextStyle, Color32, vec2};
 use egui::style::ScrollStyle;
+use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc::{Receiver, Sender};
+use streamduck_rust_client::api::ItemProperties;
 use streamduck_rust_client::base::NamespacedDeviceIdentifier;
+use streamduck_rust_client::version::ConnectionInfo;
+use streamduck_rust_client::DEFAULT_URL;
 use crate::APIMessage;
+use crate::profiles::ProfileList;
+use crate::templates::TemplateLibrary;
 use crate::ui::device_editor::{device_editor, DeviceEditor};
 use crate::ui::device_editor::input_grid::Grid;
+use crate::ui::device_editor::previews::ItemPreview;
+use crate::ui::device_editor::texture_cache::PreviewKey;
 use crate::ui::device_list::{device_list, DeviceList};
+use crate::ui::connection::{connection_screen, ConnectionScreenResponse, ConnectionStatus};
+use crate::ui::incompatible::incompatible_screen;
+use crate::ui::notifications::{notification_drawer, Notification, Notifications, toasts};
+use crate::ui::operations::{OperationId, Operations, PendingOperation};
+use crate::ui::persistence::{PersistedSettings, SettingsError};
+use crate::ui::settings::{Settings, settings_screen};
 use crate::ui::util::send_ui_message;
 
-pub fn ui_main(tx: Sender<UIMessage>, rx: Receiver<APIMessage>, notify: Receiver<()>) {
+/// `url_override` takes priority over the URL that was used last time
+pub fn ui_main(tx: Sender<UIRequest>, rx: Receiver<APIMessage>, notify: Receiver<()>, url_override: Option<String>) {
     let mut native_options = NativeOptions::default();
     native_options.viewport.min_inner_size = Some(vec2(800.0, 600.0));
     run_native("Streamduck GUI", native_options,
-               Box::new(move |cc| Box::new(UIApp::new(cc, tx, rx, notify))))
+               Box::new(move |cc| Box::new(UIApp::new(cc, tx, rx, notify, url_override))))
         .unwrap();
 }
 
@@ -36,17 +56,44 @@ pub enum UIMessage {
     },
     ConnectDevice(NamespacedDeviceIdentifier),
     GetDeviceState(NamespacedDeviceIdentifier),
-    PopScreen(NamespacedDeviceIdentifier)
+    RefreshScreen(NamespacedDeviceIdentifier),
+    PopScreen(NamespacedDeviceIdentifier),
+    /// Pops screens until the screen at the depth is on top
+    PopToDepth {
+        identifier: NamespacedDeviceIdentifier,
+        depth: usize
+    },
+    PushScreen(NamespacedDeviceIdentifier),
+    RetryConnection,
+    /// Connects to the URL, replacing current client if there's one
+    SwitchConnection(String),
+    GetItemProperties {
+        identifier: NamespacedDeviceIdentifier,
+        input: usize
+    },
+    SetItemProperties {
+        identifier: NamespacedDeviceIdentifier,
+        input: usize,
+        properties: ItemProperties
+    }
+}
+
+/// Message with the id that its completion gets reported with
+pub struct UIRequest {
+    pub id: OperationId,
+    pub message: UIMessage
 }
 
 struct UIApp {
-    tx: Sender<UIMessage>,
+    tx: Sender<UIRequest>,
     rx: Receiver<APIMessage>,
-    state: UIState
+    state: UIState,
+    /// Off if stored settings are from a newer version, so they aren't lost
+    save_settings: bool
 }
 
 impl UIApp {
-    fn new(cc: &CreationContext<'_>, tx: Sender<UIMessage>, rx: Receiver<APIMessage>, mut notify: Receiver<()>) -> Self {
+    fn new(cc: &CreationContext<'_>, tx: Sender<UIRequest>, rx: Receiver<APIMessage>, mut notify: Receiver<()>, url_override: Option<String>) -> Self {
         // Fonts
         let mut fonts = FontDefinitions::default();
 
@@ -80,7 +127,7 @@ impl UIApp {
             font_data
         );
 
-        let mut proportional = fonts.families.get_mut(&FontFamily::Proportional)
+        let proportional = fonts.families.get_mut(&FontFamily::Proportional)
             .unwrap();
 
         proportional.insert(0, "cousine-propo".to_string());
@@ -127,13 +174,189 @@ impl UIApp {
             }
         });
 
+        let (profiles, profiles_error) = ProfileList::load(ProfileList::default_path());
+        let (templates, broken_templates) = TemplateLibrary::load(TemplateLibrary::default_directory());
+        let (persisted, settings_error) = PersistedSettings::load(cc.storage);
+
+        let mut state = UIState {
+            device_list: Default::default(),
+            device_editor: Default::default(),
+            current_page: Pages::DeviceList,
+            connection_info: None,
+            connection: None,
+            settings: Default::default(),
+            profiles,
+            templates,
+            notifications: Default::default(),
+            operations: Default::default(),
+            reopen_device: None,
+            saved_url: None
+        };
+
+        if let Some(error) = profiles_error {
+            state.notifications.push(Notification::error("Failed to load connection profiles").with_details(error));
+        }
+
+        for (path, error) in broken_templates {
+            state.notifications.push(Notification::error("Failed to load template")
+                .with_details(format!("{}: {error}", path.display())));
+        }
+
+        let save_settings = !matches!(settings_error, Some(SettingsError::Newer(_)));
+
+        if let Some(error) = settings_error {
+            state.notifications.push(Notification::error("Failed to load settings, using defaults").with_details(error));
+        }
+
+        let url = url_override
+            .or(persisted.url.clone())
+            .unwrap_or_else(|| DEFAULT_URL.to_string());
+        state.restore(persisted, &url);
+
+        let id = send_ui_message(&tx, UIMessage::SwitchConnection(url));
+        state.operations.start(id, PendingOperation::SwitchConnection);
+
         Self {
             tx,
             rx,
-            state: UIState {
-                device_list: Default::default(),
-                device_editor: Default::default(),
-                current_page: Pages::DeviceList,
+            state,
+            save_settings
+        }
+    }
+
+    fn handle_message(&mut self, ctx: &Context, message: APIMessage) {
+        match message {
+            APIMessage::ConnectionInfo(info) => {
+                self.state.connection_info = Some(info);
+            }
+
+            APIMessage::ConnectionState { url, state } => {
+                // Everything that was retrieved belongs to the previous daemon
+                if self.state.connection.as_ref().is_some_and(|status| status.url != url) {
+                    self.state.forget_daemon();
+                }
+
+                self.state.connection = Some(ConnectionStatus {
+                    url,
+                    state
+                });
+            }
+
+            APIMessage::DeviceList(devices) => {
+                self.state.device_editor.connected = devices.iter()
+                    .any(|d| d.identifier == self.state.device_editor.device && d.connected);
+
+                self.state.device_list.devices = devices;
+                self.state.reopen_if_connected(&self.tx);
+            }
+            APIMessage::InputGrid { identifier, grid } => {
+                if self.state.device_editor.device != identifier {
+                    return;
+                }
+
+                self.state.device_editor.grid_failed = false;
+                self.state.device_editor.grid = Some(Grid::from_inputs(grid));
+            }
+            
+            APIMessage::Stack { identifier, stack } => {
+                let editor = &mut self.state.device_editor;
+
+                // Reply for a device that isn't open anymore, its cached previews are checked by content when reopened
+                if editor.device != identifier {
+                    return;
+                }
+
+                // Screens past the common part were popped or replaced
+                let unchanged = editor.stack.iter()
+                    .zip(&stack)
+                    .take_while(|(old, new)| old == new)
+                    .count();
+                editor.texture_cache.invalidate_from_depth(&editor.device, unchanged);
+
+                // Properties belong to the screen that was open
+                if unchanged != stack.len() || unchanged != editor.stack.len() {
+                    editor.properties.clear();
+                }
+
+                editor.stack = stack;
+            }
+            
+            APIMessage::ScreenItems { identifier, items } => {
+                let editor = &mut self.state.device_editor;
+
+                if editor.device != identifier {
+                    return;
+                }
+                let depth = editor.depth();
+
+                let mut failed = vec![];
+
+                editor.screen_items = items.clone();
+                editor.items = items.into_iter()
+                    .enumerate()
+                    .map(|(index, item)| {
+                        let key = PreviewKey {
+                            device: editor.device.clone(),
+                            depth,
+                            index
+                        };
+
+                        let (preview, error) = ItemPreview::load(ctx, &mut editor.texture_cache, key, item);
+                        if let Some(error) = error {
+                            failed.push(format!("Input {}: {error}", index + 1));
+                        }
+
+                        preview
+                    })
+                    .collect();
+
+                // Single notification for the whole screen, broken previews tend to come together
+                if !failed.is_empty() {
+                    self.state.notifications.push(Notification::error("Failed to decode previews").with_details(failed.join("\n")));
+                }
+            }
+
+            APIMessage::ItemProperties { identifier, input, properties } => {
+                if self.state.device_editor.device == identifier {
+                    self.state.device_editor.properties.loaded(input, properties);
+                }
+            }
+
+            APIMessage::ItemChanged { identifier, indices } => {
+                let editor = &mut self.state.device_editor;
+
+                if editor.device == identifier {
+                    let depth = editor.depth();
+
+                    match indices {
+                        Some(indices) => for index in indices {
+                            editor.texture_cache.invalidate(&PreviewKey {
+                                device: identifier.clone(),
+                                depth,
+                                index
+                            });
+                            editor.properties.invalidate(index);
+                        }
+                        None => {
+                            editor.texture_cache.invalidate_screen(&identifier, depth);
+                            editor.properties.clear();
+                        }
+                    }
+
+                    send_ui_message(&self.tx, UIMessage::RefreshScreen(identifier));
+                }
+            }
+
+            APIMessage::Notification(notification) => {
+                self.state.notifications.push(notification);
+            }
+
+            APIMessage::Completed { id, success } => {
+                if let Some(operation) = self.state.operations.finish(id) {
+                    if !success {
+                        self.state.rollback(operation);
+                    }
+                }
             }
         }
     }
@@ -142,77 +365,203 @@ impl UIApp {
 pub struct UIState {
     pub device_list: DeviceList,
     pub device_editor: DeviceEditor,
-    pub current_page: Pages
+    pub current_page: Pages,
+    pub connection_info: Option<ConnectionInfo>,
+    pub connection: Option<ConnectionStatus>,
+    pub settings: Settings,
+    pub profiles: ProfileList,
+    pub templates: TemplateLibrary,
+    pub notifications: Notifications,
+    pub operations: Operations,
+    /// Device from last session, that gets opened as soon as it's connected
+    pub reopen_device: Option<NamespacedDeviceIdentifier>,
+    /// URL that was picked in settings or with a profile, the one from `--url` isn't remembered
+    pub saved_url: Option<String>
 }
 
 impl UIState {
-    pub fn open_device(&mut self, sender: &Sender<UIMessage>, identifier: NamespacedDeviceIdentifier, connected: bool) {
+    pub fn open_device(&mut self, sender: &Sender<UIRequest>, identifier: NamespacedDeviceIdentifier, connected: bool) {
+        self.reopen_device = None;
+
         if !connected {
-            send_ui_message(sender, UIMessage::ConnectDevice(identifier));
+            let id = send_ui_message(sender, UIMessage::ConnectDevice(identifier.clone()));
+            self.operations.start(id, PendingOperation::ConnectDevice(identifier));
         } else {
             self.current_page = Pages::DeviceEditor;
             self.device_editor.device = identifier;
             self.device_editor.connected = connected;
-            self.device_editor.waiting_for_grid = false;
+            self.device_editor.grid_failed = false;
             self.device_editor.grid = None;
+            self.device_editor.items.clear();
+            self.device_editor.screen_items.clear();
+            self.device_editor.selection.clear();
+            self.device_editor.properties.clear();
+        }
+    }
+
+    pub fn open_settings(&mut self) {
+        self.settings.url = self.connection.as_ref()
+            .map(|status| status.url.clone())
+            .unwrap_or_default();
+        self.current_page = Pages::Settings;
+    }
+
+    pub fn close_settings(&mut self) {
+        self.current_page = Pages::DeviceList;
+    }
+
+    pub fn switch_connection(&mut self, sender: &Sender<UIRequest>, url: String) {
+        self.saved_url = Some(url.clone());
+
+        let id = send_ui_message(sender, UIMessage::SwitchConnection(url));
+        self.operations.start(id, PendingOperation::SwitchConnection);
+    }
+
+    /// Undoes optimistic update of the operation that failed
+    fn rollback(&mut self, operation: PendingOperation) {
+        match operation {
+            PendingOperation::Autoconnect { identifier, previous } => {
+                if let Some(device) = self.device_list.devices.iter_mut().find(|d| d.identifier == identifier) {
+                    device.autoconnect = previous;
+                }
+            }
+
+            PendingOperation::LoadProperties { identifier, input } => {
+                if self.device_editor.device == identifier {
+                    self.device_editor.properties.load_failed(input);
+                }
+            }
+
+            PendingOperation::SaveProperties { identifier, input, properties } => {
+                if self.device_editor.device == identifier {
+                    self.device_editor.properties.save_failed(input, properties);
+                }
+            }
+
+            // Screen refresh can fail after inputs arrived, grid is fine then
+            PendingOperation::GetDeviceState(identifier) => {
+                if self.device_editor.device == identifier && self.device_editor.grid.is_none() {
+                    self.device_editor.grid_failed = true;
+                }
+            }
+
+            PendingOperation::ConnectDevice(_) |
+            PendingOperation::SwitchConnection |
+            PendingOperation::ChangeStack(_) => {}
+        }
+    }
+
+    /// Clears devices and editor, for when the client is replaced
+    fn forget_daemon(&mut self) {
+        self.device_list = Default::default();
+        self.device_editor = Default::default();
+        self.connection_info = None;
+        self.reopen_device = None;
+
+        if let Pages::DeviceEditor = self.current_page {
+            self.current_page = Pages::DeviceList;
+        }
+    }
+
+    /// Applies settings from last session, editor is reopened later once the device is connected
+    fn restore(&mut self, persisted: PersistedSettings, url: &str) {
+        self.device_editor.grid_collapsed = persisted.grid_collapsed;
+        self.device_editor.tab = persisted.tab;
+        self.saved_url = persisted.url;
+
+        match persisted.page {
+            Pages::DeviceList => {}
+            Pages::DeviceEditor => self.reopen_device = persisted.device,
+            Pages::Settings => {
+                self.current_page = Pages::Settings;
+                self.settings.url = url.to_string();
+            }
+        }
+    }
+
+    fn reopen_if_connected(&mut self, sender: &Sender<UIRequest>) {
+        let Some(identifier) = &self.reopen_device else {
+            return;
+        };
+
+        if self.device_list.devices.iter().any(|d| &d.identifier == identifier && d.connected) {
+            let identifier = identifier.clone();
+            self.open_device(sender, identifier, true);
+        }
+    }
+
+    fn persisted(&self) -> PersistedSettings {
+        // Device that wasn't reopened yet is still the one user was editing
+        let (page, device) = match (&self.current_page, &self.reopen_device) {
+            (Pages::DeviceEditor, _) => (Pages::DeviceEditor, Some(self.device_editor.device.clone())),
+            (Pages::DeviceList, Some(device)) => (Pages::DeviceEditor, Some(device.clone())),
+            (page, _) => (*page, None)
+        };
+
+        PersistedSettings {
+            page,
+            device,
+            grid_collapsed: self.device_editor.grid_collapsed,
+            tab: self.device_editor.tab,
+            url: self.saved_url.clone(),
+            ..Default::default()
         }
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Pages {
     DeviceList,
-    DeviceEditor
+    DeviceEditor,
+    Settings
 }
 
 impl App for UIApp {
+    fn save(&mut self, storage: &mut dyn Storage) {
+        if !self.save_settings {
+            return;
+        }
+
+        if let Err(error) = self.state.persisted().save(storage) {
+            self.state.notifications.push(Notification::error("Failed to save settings").with_details(error));
+        }
+    }
+
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
-        if let Ok(message) = self.rx.try_recv() {
-            match message {
-                APIMessage::DeviceList(devices) => {
-                    self.state.device_list.devices = devices;
-                }
+        // Applying everything that arrived since last frame
+        while let Ok(message) = self.rx.try_recv() {
+            self.handle_message(ctx, message);
+        }
 
-                APIMessage::NewDevice(device) => {
-                    self.state.device_list.devices.push(device);
-                }
-                APIMessage::DeviceGone(device) => {
-                    self.state.device_list.devices.retain(|d| d.identifier != device)
-                }
-                APIMessage::ConnectedDevice(device) => {
-                    self.state.device_list.devices.iter_mut()
-                        .filter(|d| d.identifier == device.identifier)
-                        .for_each(|d| d.connected = true);
+        notification_drawer(ctx, &mut self.state.notifications);
 
-                    if self.state.device_editor.device == device.identifier {
-                        self.state.device_editor.connected = true;
-                    }
+        CentralPanel::default()
+            .show(ctx, |ui| {
+                // Settings have to be reachable while disconnected, address could be wrong
+                if let Pages::Settings = self.state.current_page {
+                    settings_screen(ui, &mut self.state, &self.tx);
+                    return;
                 }
-                APIMessage::DisconnectedDevice(device) => {
-                    self.state.device_list.devices.retain(|d| d.identifier != device);
 
-                    if self.state.device_editor.device == device {
-                        self.state.device_editor.connected = false;
+                if let Some(status) = self.state.connection.as_ref().filter(|status| !status.state.is_connected()) {
+                    if let ConnectionScreenResponse::OpenSettings = connection_screen(ui, status, &self.tx) {
+                        self.state.open_settings();
                     }
+                    return;
                 }
-                APIMessage::InputGrid(grid) => {
-                    self.state.device_editor.waiting_for_grid = false;
-                    self.state.device_editor.grid = Some(Grid::from_inputs(grid));
-                }
-                
-                APIMessage::Stack(stack) => {
-                    self.state.device_editor.stack = stack;
+
+                if let Some(info) = self.state.connection_info.as_ref().filter(|info| info.is_incompatible()) {
+                    incompatible_screen(ui, info);
+                    return;
                 }
-                
-                APIMessage::ScreenItems(_) => {}
-            }
-        }
 
-        CentralPanel::default()
-            .show(ctx, |ui| {
                 match &self.state.current_page {
                     Pages::DeviceList => device_list(ui, &mut self.state, &self.tx),
-                    Pages::DeviceEditor => device_editor(ui, &mut self.state, &self.tx)
+                    Pages::DeviceEditor => device_editor(ui, &mut self.state, &self.tx),
+                    Pages::Settings => {}
                 }
             });
+
+        toasts(ctx, &mut self.state.notifications);
     }
 }
\ No newline at end of file
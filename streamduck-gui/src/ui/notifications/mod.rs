@@ -0,0 +1,242 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use egui::{Align, Align2, Area, Button, Color32, Context, Frame, Layout, Order, RichText, ScrollArea, SidePanel, Stroke, Ui, vec2};
+
+/// How long toasts stay on screen
+const INFO_DURATION: Duration = Duration::from_secs(4);
+const ERROR_DURATION: Duration = Duration::from_secs(10);
+
+/// How many notifications the history keeps
+const HISTORY_LIMIT: usize = 100;
+
+const TOAST_WIDTH: f32 = 300.0;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum NotificationLevel {
+    Info,
+    Error
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub level: NotificationLevel,
+    pub title: String,
+    pub details: Option<String>
+}
+
+impl Notification {
+    pub fn info(title: impl Into<String>) -> Self {
+        Self {
+            level: NotificationLevel::Info,
+            title: title.into(),
+            details: None
+        }
+    }
+
+    pub fn error(title: impl Into<String>) -> Self {
+        Self {
+            level: NotificationLevel::Error,
+            title: title.into(),
+            details: None
+        }
+    }
+
+    pub fn with_details(self, details: impl ToString) -> Self {
+        Self {
+            details: Some(details.to_string()),
+            ..self
+        }
+    }
+
+    fn duration(&self) -> Duration {
+        match self.level {
+            NotificationLevel::Info => INFO_DURATION,
+            NotificationLevel::Error => ERROR_DURATION
+        }
+    }
+}
+
+struct Entry {
+    notification: Notification,
+    created: Instant,
+    dismissed: bool
+}
+
+impl Entry {
+    fn remaining(&self) -> Option<Duration> {
+        if self.dismissed {
+            return None;
+        }
+
+        self.notification.duration().checked_sub(self.created.elapsed())
+    }
+}
+
+/// Toasts that are on screen and history of all notifications
+#[derive(Default)]
+pub struct Notifications {
+    /// Oldest first
+    entries: VecDeque<Entry>,
+    unread: usize,
+    drawer_open: bool
+}
+
+impl Notifications {
+    pub fn push(&mut self, notification: Notification) {
+        self.entries.push_back(Entry {
+            notification,
+            created: Instant::now(),
+            dismissed: self.drawer_open
+        });
+
+        if !self.drawer_open {
+            self.unread += 1;
+        }
+
+        while self.entries.len() > HISTORY_LIMIT {
+            self.entries.pop_front();
+        }
+    }
+
+    fn toggle_drawer(&mut self) {
+        self.drawer_open = !self.drawer_open;
+
+        // Everything is visible in the drawer, so toasts aren't needed
+        if self.drawer_open {
+            self.unread = 0;
+            self.entries.iter_mut().for_each(|entry| entry.dismissed = true);
+        }
+    }
+}
+
+/// History drawer, has to be shown before the central panel
+pub fn notification_drawer(ctx: &Context, notifications: &mut Notifications) {
+    if !notifications.drawer_open {
+        return;
+    }
+
+    SidePanel::right("notification-history")
+        .resizable(false)
+        .exact_width(TOAST_WIDTH + 20.0)
+        .frame(Frame::default()
+            .fill(Color32::from_rgb(30, 30, 30))
+            .inner_margin(10.0))
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("Notifications");
+
+                let clear = ui.add_enabled(!notifications.entries.is_empty(), Button::new("\u{f1f8}"))
+                    .on_hover_text("Clear history");
+
+                if clear.clicked() {
+                    notifications.entries.clear();
+                }
+            });
+            ui.add_space(5.0);
+
+            if notifications.entries.is_empty() {
+                ui.label(RichText::new("Nothing happened yet").weak());
+                return;
+            }
+
+            ScrollArea::vertical()
+                .show(ui, |ui| {
+                    for entry in notifications.entries.iter().rev() {
+                        notification_frame(ui, &entry.notification, |ui| {
+                            ui.label(RichText::new(time_ago(entry.created)).small().weak());
+                        });
+                        ui.add_space(5.0);
+                    }
+                });
+        });
+}
+
+/// Toasts and the button that opens the history drawer
+pub fn toasts(ctx: &Context, notifications: &mut Notifications) {
+    Area::new("notification-bell".into())
+        .order(Order::Foreground)
+        .anchor(Align2::RIGHT_TOP, vec2(-20.0, 20.0))
+        .show(ctx, |ui| {
+            let text = match notifications.unread {
+                0 => "\u{f0f3}".to_string(),
+                unread => format!("\u{f0f3} {unread}")
+            };
+
+            if ui.add(Button::new(RichText::new(text).size(18.0))
+                .min_size(vec2(40.0, 40.0))
+                .rounding(8.0)).on_hover_text("Notifications").clicked() {
+                notifications.toggle_drawer();
+            }
+        });
+
+    let mut next_expiry: Option<Duration> = None;
+
+    Area::new("notification-toasts".into())
+        .order(Order::Foreground)
+        .anchor(Align2::RIGHT_BOTTOM, vec2(-20.0, -20.0))
+        .show(ctx, |ui| {
+            ui.set_width(TOAST_WIDTH);
+
+            for entry in notifications.entries.iter_mut() {
+                let Some(remaining) = entry.remaining() else {
+                    continue;
+                };
+
+                next_expiry = Some(next_expiry.map_or(remaining, |next| next.min(remaining)));
+
+                notification_frame(ui, &entry.notification, |ui| {
+                    if ui.small_button("\u{f00d}").on_hover_text("Dismiss").clicked() {
+                        entry.dismissed = true;
+                    }
+                });
+                ui.add_space(5.0);
+            }
+        });
+
+    if let Some(remaining) = next_expiry {
+        ctx.request_repaint_after(remaining);
+    }
+}
+
+fn notification_frame(ui: &mut Ui, notification: &Notification, add_corner: impl FnOnce(&mut Ui)) {
+    let (icon, color) = match notification.level {
+        NotificationLevel::Info => ("\u{f05a}", ui.style().visuals.selection.bg_fill),
+        NotificationLevel::Error => ("\u{f06a}", ui.style().visuals.error_fg_color)
+    };
+
+    Frame::default()
+        .fill(Color32::from_rgb(40, 40, 40))
+        .stroke(Stroke::new(1.0, color))
+        .rounding(10.0)
+        .inner_margin(10.0)
+        .show(ui, |ui| {
+            ui.set_width(TOAST_WIDTH - 20.0);
+
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(icon).color(color).size(18.0));
+                ui.label(RichText::new(&notification.title).strong());
+
+                ui.with_layout(Layout::right_to_left(Align::Center), add_corner);
+            });
+
+            if let Some(details) = &notification.details {
+                ui.label(RichText::new(details).weak());
+            }
+        });
+}
+
+fn time_ago(instant: Instant) -> String {
+    let seconds = instant.elapsed().as_secs();
+
+    match seconds {
+        0..=59 => format!("{seconds}s ago"),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        _ => format!("{}h ago", seconds / 3600)
+    }
+}
@@ -0,0 +1,67 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use streamduck_rust_client::api::ItemProperties;
+use streamduck_rust_client::base::NamespacedDeviceIdentifier;
+
+/// Identifies a sent [UIMessage](crate::ui::UIMessage), its completion is reported with the same id
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+pub struct OperationId(u64);
+
+impl OperationId {
+    pub fn next() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// Operation that UI is waiting on, with what's needed to undo its optimistic update
+#[derive(Debug, Clone, PartialEq)]
+pub enum PendingOperation {
+    Autoconnect {
+        identifier: NamespacedDeviceIdentifier,
+        previous: bool
+    },
+    ConnectDevice(NamespacedDeviceIdentifier),
+    /// Retrieving input layout and current screen
+    GetDeviceState(NamespacedDeviceIdentifier),
+    SwitchConnection,
+    LoadProperties {
+        identifier: NamespacedDeviceIdentifier,
+        input: usize
+    },
+    SaveProperties {
+        identifier: NamespacedDeviceIdentifier,
+        input: usize,
+        properties: ItemProperties
+    },
+    /// Pushing or popping screens
+    ChangeStack(NamespacedDeviceIdentifier)
+}
+
+/// Operations that were sent, but didn't complete yet
+#[derive(Default)]
+pub struct Operations {
+    pending: HashMap<OperationId, PendingOperation>
+}
+
+impl Operations {
+    pub fn start(&mut self, id: OperationId, operation: PendingOperation) {
+        self.pending.insert(id, operation);
+    }
+
+    /// Removes the operation, `None` if it wasn't tracked
+    pub fn finish(&mut self, id: OperationId) -> Option<PendingOperation> {
+        self.pending.remove(&id)
+    }
+
+    /// If any pending operation matches
+    pub fn any(&self, predicate: impl Fn(&PendingOperation) -> bool) -> bool {
+        self.pending.values().any(predicate)
+    }
+}
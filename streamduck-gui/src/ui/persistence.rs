@@ -0,0 +1,202 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::fmt::{Display, Formatter};
+use eframe::Storage;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use streamduck_rust_client::base::NamespacedDeviceIdentifier;
+use crate::ui::device_editor::EditorTab;
+use crate::ui::Pages;
+
+/// Key of the settings in eframe's storage
+const SETTINGS_KEY: &str = "streamduck-settings";
+
+/// Version of [PersistedSettings] that is currently written, bumping it requires adding a migration
+pub const SETTINGS_VERSION: u64 = 0;
+
+/// Migrations of older settings, entry at index N upgrades version N to version N + 1
+const MIGRATIONS: [fn(&mut Value); SETTINGS_VERSION as usize] = [];
+
+/// Why stored settings weren't used
+#[derive(Debug)]
+pub enum SettingsError {
+    /// Settings are broken and get replaced with defaults on next save
+    Invalid(String),
+    /// Settings were written by a newer version, they shouldn't be overwritten
+    Newer(u64)
+}
+
+impl Display for SettingsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingsError::Invalid(error) => write!(f, "{error}"),
+            SettingsError::Newer(version) => write!(f, "Settings are from newer version {version}, they won't be saved over")
+        }
+    }
+}
+
+/// UI state that survives restarts
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct PersistedSettings {
+    pub version: u64,
+    pub page: Pages,
+    /// Device that was open in the editor
+    pub device: Option<NamespacedDeviceIdentifier>,
+    pub grid_collapsed: bool,
+    pub tab: EditorTab,
+    /// Daemon URL that was picked in the UI
+    pub url: Option<String>
+}
+
+impl Default for PersistedSettings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            page: Pages::DeviceList,
+            device: None,
+            grid_collapsed: false,
+            tab: Default::default(),
+            url: None
+        }
+    }
+}
+
+impl PersistedSettings {
+    /// Reads settings from the storage, upgrading them if they're from older version
+    ///
+    /// Falls back to defaults if settings are missing, broken or from newer version, error is returned for the last two
+    pub fn load(storage: Option<&dyn Storage>) -> (Self, Option<SettingsError>) {
+        let Some(json) = storage.and_then(|storage| storage.get_string(SETTINGS_KEY)) else {
+            return (Default::default(), None);
+        };
+
+        match parse(&json) {
+            Ok(settings) => (settings, None),
+            Err(error) => (Default::default(), Some(error))
+        }
+    }
+
+    pub fn save(&self, storage: &mut dyn Storage) -> serde_json::Result<()> {
+        storage.set_string(SETTINGS_KEY, serde_json::to_string(self)?);
+        Ok(())
+    }
+}
+
+fn parse(json: &str) -> Result<PersistedSettings, SettingsError> {
+    let mut value: Value = serde_json::from_str(json).map_err(|error| SettingsError::Invalid(error.to_string()))?;
+
+    let version = value.get("Version")
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+
+    if version > SETTINGS_VERSION {
+        return Err(SettingsError::Newer(version));
+    }
+
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(&mut value);
+    }
+
+    if let Some(object) = value.as_object_mut() {
+        object.insert("Version".to_string(), SETTINGS_VERSION.into());
+    }
+
+    serde_json::from_value(value).map_err(|error| SettingsError::Invalid(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use eframe::Storage;
+    use serde_json::json;
+    use crate::ui::Pages;
+    use super::{PersistedSettings, SettingsError, SETTINGS_KEY, SETTINGS_VERSION};
+
+    #[derive(Default)]
+    struct MemoryStorage(HashMap<String, String>);
+
+    impl Storage for MemoryStorage {
+        fn get_string(&self, key: &str) -> Option<String> {
+            self.0.get(key).cloned()
+        }
+
+        fn set_string(&mut self, key: &str, value: String) {
+            self.0.insert(key.to_string(), value);
+        }
+
+        fn flush(&mut self) {}
+    }
+
+    fn storage_with(json: &str) -> MemoryStorage {
+        let mut storage = MemoryStorage::default();
+        storage.set_string(SETTINGS_KEY, json.to_string());
+        storage
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let settings = PersistedSettings {
+            page: Pages::Settings,
+            grid_collapsed: true,
+            url: Some("ws://localhost:42131".to_string()),
+            ..Default::default()
+        };
+
+        let mut storage = MemoryStorage::default();
+        settings.save(&mut storage).unwrap();
+
+        let (loaded, error) = PersistedSettings::load(Some(&storage));
+        assert!(error.is_none());
+        assert_eq!(loaded.page, Pages::Settings);
+        assert!(loaded.grid_collapsed);
+        assert_eq!(loaded.url, settings.url);
+    }
+
+    #[test]
+    fn missing_settings_are_defaults_without_error() {
+        let (loaded, error) = PersistedSettings::load(Some(&MemoryStorage::default()));
+
+        assert!(error.is_none());
+        assert_eq!(loaded.page, Pages::DeviceList);
+    }
+
+    #[test]
+    fn corrupt_settings_fall_back_to_defaults() {
+        for json in ["not json", r#"{"Page": 5}"#, "42"] {
+            let (loaded, error) = PersistedSettings::load(Some(&storage_with(json)));
+
+            assert!(matches!(error, Some(SettingsError::Invalid(_))), "{json} wasn't rejected");
+            assert_eq!(loaded.page, Pages::DeviceList);
+            assert!(loaded.url.is_none());
+        }
+    }
+
+    #[test]
+    fn settings_without_version_are_upgraded() {
+        let json = json!({ "Page": "Settings", "Url": "ws://example:1" }).to_string();
+        let (loaded, error) = PersistedSettings::load(Some(&storage_with(&json)));
+
+        assert!(error.is_none());
+        assert_eq!(loaded.version, SETTINGS_VERSION);
+        assert_eq!(loaded.page, Pages::Settings);
+        assert_eq!(loaded.url.as_deref(), Some("ws://example:1"));
+    }
+
+    #[test]
+    fn newer_settings_are_rejected() {
+        let newer = SETTINGS_VERSION + 1;
+        let json = json!({ "Version": newer, "Page": "Settings", "Url": "ws://example:1" }).to_string();
+        let storage = storage_with(&json);
+
+        let (loaded, error) = PersistedSettings::load(Some(&storage));
+
+        assert!(matches!(error, Some(SettingsError::Newer(version)) if version == newer));
+        assert!(loaded.url.is_none());
+        assert_eq!(storage.get_string(SETTINGS_KEY), Some(json));
+    }
+}
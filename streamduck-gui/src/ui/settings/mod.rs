@@ -0,0 +1,171 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use egui::{Button, Color32, Frame, RichText, ScrollArea, TextEdit, Ui, vec2};
+use tokio::sync::mpsc::Sender;
+use streamduck_rust_client::connection::ConnectionState;
+use crate::profiles::ConnectionProfile;
+use crate::ui::notifications::Notification;
+use crate::ui::operations::PendingOperation;
+use crate::ui::{UIRequest, UIState};
+
+#[derive(Default)]
+pub struct Settings {
+    pub url: String,
+    pub profile_name: String
+}
+
+pub fn settings_screen(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIRequest>) {
+    Frame::default()
+        .fill(Color32::from_rgb(40, 40, 40))
+        .rounding(10.0)
+        .inner_margin(10.0)
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                if ui.add(Button::new(RichText::new("\u{f17a7}").size(30.0).line_height(Some(32.0)))
+                    .min_size(vec2(60.0, 50.0))
+                    .rounding(8.0)).clicked() {
+                    state.close_settings();
+                }
+
+                ui.heading(RichText::new("Connection Settings")
+                    .size(36.0));
+            });
+        });
+
+    ui.add_space(2.0);
+
+    Frame::default()
+        .fill(Color32::from_rgb(40, 40, 40))
+        .rounding(10.0)
+        .inner_margin(10.0)
+        .show(ui, |ui| {
+            ScrollArea::vertical()
+                .show(ui, |ui| {
+                    current_connection_ui(ui, state);
+                    ui.add_space(10.0);
+
+                    url_ui(ui, state, sender);
+                    ui.add_space(20.0);
+
+                    profiles_ui(ui, state, sender);
+
+                    ui.allocate_space(ui.available_size());
+                });
+        });
+}
+
+fn current_connection_ui(ui: &mut Ui, state: &UIState) {
+    let Some(status) = &state.connection else {
+        return;
+    };
+
+    let description = match &status.state {
+        ConnectionState::Connecting => "Connecting to",
+        ConnectionState::Connected => "Connected to",
+        ConnectionState::Reconnecting { .. } => "Trying to reach",
+        ConnectionState::Closed { .. } => "Couldn't connect to"
+    };
+
+    ui.horizontal(|ui| {
+        ui.label(description);
+        ui.label(RichText::new(&status.url).monospace());
+    });
+}
+
+fn url_ui(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIRequest>) {
+    ui.label(RichText::new("Daemon address").strong());
+
+    ui.horizontal(|ui| {
+        ui.add(TextEdit::singleline(&mut state.settings.url)
+            .hint_text("ws://127.0.0.1:42131")
+            .desired_width(ui.available_width() - 90.0));
+
+        let valid_url = !state.settings.url.trim().is_empty();
+        let switching = state.operations.any(|operation| operation == &PendingOperation::SwitchConnection);
+
+        if switching {
+            ui.spinner();
+        } else if ui.add_enabled(valid_url, Button::new("Connect").min_size(vec2(80.0, 0.0))).clicked() {
+            let url = state.settings.url.trim().to_string();
+            state.switch_connection(sender, url);
+        }
+    });
+}
+
+fn profiles_ui(ui: &mut Ui, state: &mut UIState, sender: &Sender<UIRequest>) {
+    ui.label(RichText::new("Profiles").strong());
+
+    ui.horizontal(|ui| {
+        ui.add(TextEdit::singleline(&mut state.settings.profile_name)
+            .hint_text("Profile name")
+            .desired_width(ui.available_width() - 130.0));
+
+        let valid = !state.settings.profile_name.trim().is_empty() && !state.settings.url.trim().is_empty();
+
+        if ui.add_enabled(valid, Button::new("Save address as profile")).clicked() {
+            let profile = ConnectionProfile {
+                name: state.settings.profile_name.trim().to_string(),
+                url: state.settings.url.trim().to_string()
+            };
+
+            match state.profiles.save(profile) {
+                Ok(()) => state.settings.profile_name.clear(),
+                Err(error) => state.notifications.push(Notification::error("Failed to save connection profile").with_details(error))
+            }
+        }
+    });
+    ui.add_space(5.0);
+
+    if state.profiles.profiles().is_empty() {
+        ui.label(RichText::new("No profiles yet, save an address to switch to it later").weak());
+        return;
+    }
+
+    let current_url = state.connection.as_ref().map(|status| status.url.as_str());
+
+    let mut connect = None;
+    let mut removed = None;
+
+    for (index, profile) in state.profiles.profiles().iter().enumerate() {
+        ui.horizontal(|ui| {
+            Frame::default()
+                .fill(Color32::from_rgb(30, 30, 30))
+                .rounding(8.0)
+                .inner_margin(5.0)
+                .show(ui, |ui| {
+                    ui.set_width(ui.available_width() - 120.0);
+
+                    ui.vertical(|ui| {
+                        ui.label(RichText::new(&profile.name).strong());
+                        ui.label(RichText::new(&profile.url).monospace().weak());
+                    });
+                });
+
+            // Client normalizes the URL, so trailing slash is ignored when comparing
+            let active = current_url.is_some_and(|url| url.trim_end_matches('/') == profile.url.trim_end_matches('/'));
+
+            if ui.add_enabled(!active, Button::new(if active { "Active" } else { "Connect" }).min_size(vec2(70.0, 30.0))).clicked() {
+                connect = Some(profile.url.clone());
+            }
+
+            if ui.add(Button::new("\u{f1f8}").min_size(vec2(30.0, 30.0))).on_hover_text("Delete profile").clicked() {
+                removed = Some(index);
+            }
+        });
+    }
+
+    if let Some(url) = connect {
+        state.settings.url = url.clone();
+        state.switch_connection(sender, url);
+    }
+
+    if let Some(index) = removed {
+        if let Err(error) = state.profiles.remove(index) {
+            state.notifications.push(Notification::error("Failed to delete connection profile").with_details(error));
+        }
+    }
+}
@@ -9,7 +9,8 @@ use egui::{Color32, Id, Response, Ui};
 use egui::style::WidgetVisuals;
 use interpolation::{Ease, Lerp};
 use tokio::sync::mpsc::Sender;
-use crate::ui::UIMessage;
+use crate::ui::{UIMessage, UIRequest};
+use crate::ui::operations::OperationId;
 
 #[derive(Copy, Clone, Debug)]
 pub struct Colorf32 {
@@ -33,10 +34,10 @@ impl From<Color32> for Colorf32 {
 impl From<Colorf32> for Color32 {
     fn from(value: Colorf32) -> Self {
         Color32::from_rgba_premultiplied(
-            (value.r.min(1.0).max(0.0) * 255.0) as u8,
-            (value.g.min(1.0).max(0.0) * 255.0) as u8,
-            (value.b.min(1.0).max(0.0) * 255.0) as u8,
-            (value.a.min(1.0).max(0.0) * 255.0) as u8
+            (value.r.clamp(0.0, 1.0) * 255.0) as u8,
+            (value.g.clamp(0.0, 1.0) * 255.0) as u8,
+            (value.b.clamp(0.0, 1.0) * 255.0) as u8,
+            (value.a.clamp(0.0, 1.0) * 255.0) as u8
         )
     }
 }
@@ -96,17 +97,11 @@ impl Sub<Colorf32> for Colorf32 {
 pub fn lerp_color(a: &Color32, b: &Color32, t: f32) -> Color32 {
     let a = Colorf32::from(*a);
     let b = Colorf32::from(*b);
-    let t = t.min(1.0).max(0.0);
+    let t = t.clamp(0.0, 1.0);
 
     (a + (b - a) * t).into()
 }
 
-pub fn lerp_f32(a: f32, b: f32, t: f32) -> f32 {
-    let t = t.min(1.0).max(0.0);
-
-    a + (b - a) * t
-}
-
 pub fn interact_lerped_selectable(ui: &Ui, response: &Response, selected: bool, id: Id, animation_time: f32) -> WidgetVisuals {
     let t = ui.ctx().animate_value_with_time(id, if selected { 1.0 } else { 0.0 }, animation_time).cubic_in_out();
 
@@ -133,9 +128,14 @@ pub fn lerped_selectable(ui: &Ui, selected: bool, id: Id, animation_time: f32) -
     visuals
 }
 
-pub fn send_ui_message(sender: &Sender<UIMessage>, message: UIMessage) {
+/// Sends the message in background, returns id that its completion will be reported with
+pub fn send_ui_message(sender: &Sender<UIRequest>, message: UIMessage) -> OperationId {
+    let id = OperationId::next();
+
     let sender = sender.clone();
     tokio::spawn(async move {
-        sender.send(message).await.ok()
+        sender.send(UIRequest { id, message }).await.ok()
     });
+
+    id
 }
\ No newline at end of file
@@ -3,156 +3,227 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
+//! Requests and types of Streamduck socket API
 
 use serde::{Deserialize, Serialize};
-use crate::base::{DeviceIdentifier, NamespacedDeviceIdentifier, NamespacedName};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use crate::base::{NamespacedDeviceIdentifier, NamespacedName};
 
-pub trait StreamduckRequest {
-    fn name(&self) -> NamespacedName;
-}
+pub use streamduck_derive::StreamduckRequest;
 
-#[derive(Serialize)]
-pub struct CoreVersion;
+/// Request that can be sent to Streamduck daemon
+///
+/// Can be derived, see [derive@StreamduckRequest]
+pub trait StreamduckRequest: Serialize {
+    /// Type the daemon responds with
+    type Response: DeserializeOwned;
 
-impl StreamduckRequest for CoreVersion {
-    fn name(&self) -> NamespacedName {
-        NamespacedName::new("Core", "Socket Version")
+    /// Name the request is registered under in the daemon
+    fn name(&self) -> NamespacedName;
+
+    /// If sending the request again has no additional effect,
+    /// such requests can be replayed after reconnecting
+    fn idempotent(&self) -> bool {
+        false
     }
 }
 
-#[derive(Serialize)]
-pub struct ListDevices;
+/// Untyped request, for calling endpoints that don't have a typed request, like ones registered by plugins
+#[derive(Serialize, Debug, Clone)]
+#[serde(transparent)]
+pub struct RawRequest {
+    /// Name the request is registered under in the daemon
+    #[serde(skip)]
+    pub name: NamespacedName,
+    /// Data of the request
+    pub data: Value
+}
+
+impl StreamduckRequest for RawRequest {
+    type Response = Value;
 
-impl StreamduckRequest for ListDevices {
     fn name(&self) -> NamespacedName {
-        NamespacedName::new("Core", "List Devices")
+        self.name.clone()
     }
 }
 
+/// Retrieves socket API version
+#[derive(Serialize, StreamduckRequest)]
+#[request(plugin = "Core", name = "Socket Version", response = String, idempotent)]
+pub struct CoreVersion;
+
+/// Lists all devices known to the daemon
+#[derive(Serialize, StreamduckRequest)]
+#[request(plugin = "Core", name = "List Devices", response = Vec<Device>, idempotent)]
+pub struct ListDevices;
+
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, Eq, PartialEq)]
 #[serde(rename_all = "PascalCase")]
+/// Device known to the daemon
 pub struct Device {
+    /// Identifier of the device
     pub identifier: NamespacedDeviceIdentifier,
+    /// If the device is currently connected
     pub connected: bool,
+    /// If the daemon automatically connects to the device
     pub autoconnect: bool
 }
 
-#[derive(Serialize)]
+/// Sets if daemon should automatically connect to the device
+#[derive(Serialize, StreamduckRequest)]
 #[serde(rename_all = "PascalCase")]
+#[request(plugin = "Core", name = "Set Device Autoconnect")]
 pub struct SetDeviceAutoconnect {
+    /// Identifier of the device
     pub identifier: NamespacedDeviceIdentifier,
+    /// New autoconnect value
     pub autoconnect: bool
 }
 
-impl StreamduckRequest for SetDeviceAutoconnect {
-    fn name(&self) -> NamespacedName {
-        NamespacedName::new("Core", "Set Device Autoconnect")
-    }
-}
-
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, Eq, PartialEq)]
 #[serde(rename_all = "PascalCase")]
+/// Input of a device, positioned on device's input grid
 pub struct Input {
+    /// X position on the grid
     pub x: i32,
+    /// Y position on the grid
     pub y: i32,
+    /// Width on the grid
     pub w: u32,
+    /// Height on the grid
     pub h: u32,
+    /// What kind of input this is
     pub icon: InputIcon
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, Eq, PartialEq)]
+/// Kind of the input
 pub enum InputIcon {
+    /// Regular button
     Button,
+    /// Button that stays pressed
     Toggle,
+    /// Pressure sensitive button
     AnalogButton,
+    /// Slider or fader
     Slider,
+    /// Knob with limited rotation
     Knob,
+    /// Endlessly rotating encoder
     Encoder,
+    /// Touch screen
     TouchScreen,
+    /// Joystick
     Joystick,
+    /// Trackball
     Trackball,
+    /// Touchpad
     Touchpad,
+    /// Any other kind of sensor
     Sensor
 }
 
-#[derive(Serialize)]
+/// Retrieves input layout of the device
+#[derive(Serialize, StreamduckRequest)]
 #[serde(rename_all = "PascalCase")]
+#[request(plugin = "Core", name = "Get Device Inputs", response = Vec<Input>, idempotent)]
 pub struct GetDeviceInputs {
+    /// Identifier of the device
     pub identifier: NamespacedDeviceIdentifier
 }
 
-impl StreamduckRequest for GetDeviceInputs {
-    fn name(&self) -> NamespacedName {
-        NamespacedName::new("Core", "Get Device Inputs")
-    }
-}
-
-#[derive(Serialize)]
+/// Connects to the device
+#[derive(Serialize, StreamduckRequest)]
 #[serde(rename_all = "PascalCase")]
+#[request(plugin = "Core", name = "Connect Device", response = bool)]
 pub struct ConnectDevice {
+    /// Identifier of the device
     pub identifier: NamespacedDeviceIdentifier
 }
 
-impl StreamduckRequest for ConnectDevice {
-    fn name(&self) -> NamespacedName {
-        NamespacedName::new("Core", "Connect Device")
-    }
-}
-
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, Eq, PartialEq)]
 #[serde(rename_all = "PascalCase")]
+/// Screen item as seen from outside of the daemon
 pub struct PartialScreenItem {
+    /// If the item is drawn onto the device
     pub renderable: bool,
+    /// Base64 encoded JPEG preview of the item, if previews were requested
     #[serde(rename = "Base64JPG")]
     pub base64jpg: Option<String>
 }
 
-#[derive(Serialize)]
+/// Retrieves screen items of the device's current screen
+#[derive(Serialize, StreamduckRequest)]
 #[serde(rename_all = "PascalCase")]
+#[request(plugin = "Core", name = "Get Device Items", response = Vec<Option<PartialScreenItem>>, idempotent)]
 pub struct GetDeviceItems {
+    /// Identifier of the device
     pub identifier: NamespacedDeviceIdentifier,
+    /// If previews of the items should be included
     pub get_previews: bool
 }
 
-impl StreamduckRequest for GetDeviceItems {
-    fn name(&self) -> NamespacedName {
-        NamespacedName::new("Core", "Get Device Items")
-    }
-}
-
-
-#[derive(Serialize)]
+/// Retrieves names of screens in device's screen stack
+#[derive(Serialize, StreamduckRequest)]
 #[serde(rename_all = "PascalCase")]
+#[request(plugin = "Core", name = "Get Device Screen Stack", response = Vec<String>, idempotent)]
 pub struct GetDeviceScreenStack {
+    /// Identifier of the device
     pub identifier: NamespacedDeviceIdentifier
 }
 
-impl StreamduckRequest for GetDeviceScreenStack {
-    fn name(&self) -> NamespacedName {
-        NamespacedName::new("Core", "Get Device Screen Stack")
-    }
+/// Pops top screen from device's screen stack
+#[derive(Serialize, StreamduckRequest)]
+#[serde(rename_all = "PascalCase")]
+#[request(plugin = "Core", name = "Pop Screen", response = bool)]
+pub struct PopScreen {
+    /// Identifier of the device
+    pub identifier: NamespacedDeviceIdentifier
 }
 
-#[derive(Serialize)]
+/// Pushes a new empty screen onto device's screen stack
+#[derive(Serialize, StreamduckRequest)]
 #[serde(rename_all = "PascalCase")]
-pub struct PopScreen {
+#[request(plugin = "Core", name = "Push New Empty Screen")]
+pub struct PushNewEmptyScreen {
+    /// Identifier of the device
     pub identifier: NamespacedDeviceIdentifier
 }
 
-impl StreamduckRequest for PopScreen {
-    fn name(&self) -> NamespacedName {
-        NamespacedName::new("Core", "Pop Screen")
-    }
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+/// Properties of a screen item
+pub struct ItemProperties {
+    /// If the item is drawn onto the device
+    pub renderable: bool,
+    /// Action that is triggered by the input, if any
+    pub action: Option<NamespacedName>,
+    /// Parameters of the action
+    #[serde(default)]
+    pub parameters: Map<String, Value>
 }
 
-#[derive(Serialize)]
+/// Retrieves properties of the item assigned to the input on device's current screen, `None` if nothing is assigned
+#[derive(Serialize, StreamduckRequest)]
 #[serde(rename_all = "PascalCase")]
-pub struct PushNewEmptyScreen {
-    pub identifier: NamespacedDeviceIdentifier
+#[request(plugin = "Core", name = "Get Item Properties", response = Option<ItemProperties>, idempotent)]
+pub struct GetItemProperties {
+    /// Identifier of the device
+    pub identifier: NamespacedDeviceIdentifier,
+    /// Index of the input
+    pub input: usize
 }
 
-impl StreamduckRequest for PushNewEmptyScreen {
-    fn name(&self) -> NamespacedName {
-        NamespacedName::new("Core", "Push New Empty Screen")
-    }
-}
\ No newline at end of file
+/// Replaces properties of the item assigned to the input on device's current screen
+#[derive(Serialize, StreamduckRequest)]
+#[serde(rename_all = "PascalCase")]
+#[request(plugin = "Core", name = "Set Item Properties", idempotent)]
+pub struct SetItemProperties {
+    /// Identifier of the device
+    pub identifier: NamespacedDeviceIdentifier,
+    /// Index of the input
+    pub input: usize,
+    /// New properties of the item
+    pub properties: ItemProperties
+}
@@ -10,12 +10,16 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, Eq, PartialEq, Default)]
 #[serde(rename_all = "PascalCase")]
+/// Name that is namespaced by plugin that owns it
 pub struct NamespacedName {
+    /// Name of the plugin
     pub plugin_name: String,
+    /// Name of the thing
     pub name: String
 }
 
 impl NamespacedName {
+    /// Creates new namespaced name
     pub fn new(plugin_name: &str, name: &str) -> NamespacedName {
         NamespacedName {
             plugin_name: plugin_name.to_string(),
@@ -32,12 +36,16 @@ impl Display for NamespacedName {
 
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, Eq, PartialEq, Default)]
 #[serde(rename_all = "PascalCase")]
+/// Identifier of a device within its driver
 pub struct DeviceIdentifier {
+    /// Unique identifier, usually a serial number
     pub identifier: String,
+    /// Human readable description of the device
     pub description: String
 }
 
 impl DeviceIdentifier {
+    /// Creates new device identifier
     pub fn new(identifier: &str, description: &str) -> DeviceIdentifier {
         DeviceIdentifier {
             identifier: identifier.to_string(),
@@ -53,9 +61,12 @@ impl Display for DeviceIdentifier {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Hash, Eq, PartialEq, Default)]
+/// Device identifier namespaced by driver that provides the device
 pub struct NamespacedDeviceIdentifier {
+    /// Name of the driver
     #[serde(rename = "NamespacedName")]
     pub name: NamespacedName,
+    /// Identifier of the device
     #[serde(rename = "DeviceIdentifier")]
     pub identifier: DeviceIdentifier
 }
@@ -0,0 +1,75 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+//! Connection state and reconnect behavior
+
+use std::time::Duration;
+
+/// State of client's connection to the daemon
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ConnectionState {
+    /// Client is making its first connection attempt
+    Connecting,
+    /// Client is connected to the daemon
+    Connected,
+    /// Client lost connection or failed to connect, and is trying again
+    Reconnecting {
+        /// Number of the upcoming attempt, starting from 1
+        attempt: usize,
+        /// Why the last attempt failed, if it did
+        last_error: Option<String>
+    },
+    /// Client gave up or was closed, it won't connect again
+    Closed {
+        /// Why the client was closed, `None` if it was closed with [Streamduck::close](crate::Streamduck::close)
+        reason: Option<String>
+    }
+}
+
+impl ConnectionState {
+    /// If the client is connected to the daemon
+    pub fn is_connected(&self) -> bool {
+        matches!(self, ConnectionState::Connected)
+    }
+
+    /// If the client won't connect again
+    pub fn is_closed(&self) -> bool {
+        matches!(self, ConnectionState::Closed { .. })
+    }
+}
+
+/// Decides how client reconnects after losing connection or failing to connect
+#[derive(Debug, Clone)]
+pub struct ReconnectConfig {
+    /// Delay after the first failed attempt
+    pub initial_delay: Duration,
+    /// Delay will never grow past this
+    pub max_delay: Duration,
+    /// Delay gets multiplied by this after every failed attempt
+    pub multiplier: f64,
+    /// Client gives up after this many failed attempts in a row, `None` tries forever
+    pub max_attempts: Option<usize>
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            multiplier: 2.0,
+            max_attempts: None
+        }
+    }
+}
+
+impl ReconnectConfig {
+    /// Delay before next attempt, after the provided amount of failed attempts
+    pub fn delay(&self, failed_attempts: usize) -> Duration {
+        let exponent = failed_attempts.saturating_sub(1).min(i32::MAX as usize) as i32;
+        let delay = self.initial_delay.as_secs_f64() * self.multiplier.max(1.0).powi(exponent);
+
+        Duration::from_secs_f64(delay.min(self.max_delay.as_secs_f64()))
+    }
+}
@@ -0,0 +1,119 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+//! Handle for working with a single device
+
+use std::future::ready;
+use std::sync::Arc;
+use futures::{Stream, StreamExt};
+use tokio::sync::Mutex;
+use crate::api::{Input, ItemProperties, PartialScreenItem};
+use crate::base::NamespacedDeviceIdentifier;
+use crate::error::Result;
+use crate::event::StreamduckEvent;
+use crate::Streamduck;
+
+/// Client scoped to a single device, created with [Streamduck::device]
+///
+/// Cloning is cheap, clones share the cached input layout
+#[derive(Clone)]
+pub struct DeviceHandle {
+    streamduck: Streamduck,
+    identifier: NamespacedDeviceIdentifier,
+    inputs: Arc<Mutex<Option<Vec<Input>>>>
+}
+
+impl DeviceHandle {
+    pub(crate) fn new(streamduck: Streamduck, identifier: NamespacedDeviceIdentifier) -> Self {
+        Self {
+            streamduck,
+            identifier,
+            inputs: Default::default()
+        }
+    }
+
+    /// Identifier of the device
+    pub fn identifier(&self) -> &NamespacedDeviceIdentifier {
+        &self.identifier
+    }
+
+    /// Client the handle is using
+    pub fn client(&self) -> &Streamduck {
+        &self.streamduck
+    }
+
+    /// Sets if daemon should automatically connect to the device
+    pub async fn set_autoconnect(&self, autoconnect: bool) -> Result<()> {
+        self.streamduck.set_device_autoconnect(self.identifier.clone(), autoconnect).await
+    }
+
+    /// Connects to the device, returns if connection was successful
+    pub async fn connect(&self) -> Result<bool> {
+        self.streamduck.connect_device(self.identifier.clone()).await
+    }
+
+    /// Input layout of the device, only retrieved from the daemon the first time
+    pub async fn inputs(&self) -> Result<Vec<Input>> {
+        let mut inputs = self.inputs.lock().await;
+
+        if let Some(inputs) = inputs.as_ref() {
+            return Ok(inputs.clone());
+        }
+
+        let fetched = self.streamduck.get_device_inputs(self.identifier.clone()).await?;
+        *inputs = Some(fetched.clone());
+
+        Ok(fetched)
+    }
+
+    /// Retrieves input layout of the device again, replacing the cached one
+    pub async fn refresh_inputs(&self) -> Result<Vec<Input>> {
+        let mut inputs = self.inputs.lock().await;
+
+        let fetched = self.streamduck.get_device_inputs(self.identifier.clone()).await?;
+        *inputs = Some(fetched.clone());
+
+        Ok(fetched)
+    }
+
+    /// Retrieves screen items of the device's current screen, optionally with previews
+    pub async fn items(&self, previews: Option<bool>) -> Result<Vec<Option<PartialScreenItem>>> {
+        self.streamduck.get_device_items(self.identifier.clone(), previews).await
+    }
+
+    /// Retrieves names of screens currently in device's screen stack
+    pub async fn screen_stack(&self) -> Result<Vec<String>> {
+        self.streamduck.get_device_screen_stack(self.identifier.clone()).await
+    }
+
+    /// Pops top screen from device's screen stack, returns if anything was popped
+    pub async fn pop_screen(&self) -> Result<bool> {
+        self.streamduck.pop_screen(self.identifier.clone()).await
+    }
+
+    /// Pushes a new empty screen onto device's screen stack
+    pub async fn push_new_empty_screen(&self) -> Result<()> {
+        self.streamduck.push_new_empty_screen(self.identifier.clone()).await
+    }
+
+    /// Retrieves properties of the item assigned to the input, `None` if nothing is assigned
+    pub async fn item_properties(&self, input: usize) -> Result<Option<ItemProperties>> {
+        self.streamduck.get_item_properties(self.identifier.clone(), input).await
+    }
+
+    /// Replaces properties of the item assigned to the input
+    pub async fn set_item_properties(&self, input: usize, properties: ItemProperties) -> Result<()> {
+        self.streamduck.set_item_properties(self.identifier.clone(), input, properties).await
+    }
+
+    /// Stream of events about this device, sent after this call
+    pub fn events(&self) -> impl Stream<Item = StreamduckEvent> + Send + Unpin + 'static {
+        let identifier = self.identifier.clone();
+
+        self.streamduck.subscribe()
+            .into_stream()
+            .filter(move |event| ready(event.device() == Some(&identifier)))
+    }
+}
@@ -0,0 +1,70 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+//! Errors returned by the client
+
+use std::time::Duration;
+use serde_json::Value;
+use thiserror::Error;
+
+/// Result type used by the client
+pub type Result<T, E = StreamduckError> = std::result::Result<T, E>;
+
+/// Everything that can go wrong while talking to Streamduck daemon
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum StreamduckError {
+    /// Provided daemon URL couldn't be parsed
+    #[error("Invalid daemon URL: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+    /// Client was closed and can't send requests anymore
+    #[error("Client is closed")]
+    ClientClosed,
+    /// Connection was lost before daemon responded
+    #[error("Connection to daemon was lost")]
+    ConnectionLost,
+    /// Daemon didn't respond in time
+    #[error("Request timed out after {0:?}")]
+    Timeout(Duration),
+    /// Daemon sent something that doesn't follow the protocol
+    #[error("Protocol error: {reason}")]
+    Protocol {
+        /// What was wrong with the message
+        reason: String,
+        /// Data of the offending message
+        payload: Value
+    },
+    /// Daemon responded with an error
+    #[error("Error received from request: {message}")]
+    Daemon {
+        /// Error message sent by the daemon
+        message: String,
+        /// Data of the response
+        payload: Value
+    },
+    /// Request couldn't be serialized
+    #[error("Failed to serialize request: {0}")]
+    Serialize(#[source] serde_json::Error),
+    /// Response didn't match the expected type
+    #[error("Failed to deserialize response: {source}")]
+    Deserialize {
+        /// What went wrong
+        source: serde_json::Error,
+        /// Data of the response
+        payload: Value
+    }
+}
+
+impl StreamduckError {
+    /// Data of the message that caused the error, if there was one
+    pub fn payload(&self) -> Option<&Value> {
+        match self {
+            StreamduckError::Protocol { payload, .. }
+            | StreamduckError::Daemon { payload, .. }
+            | StreamduckError::Deserialize { payload, .. } => Some(payload),
+            _ => None
+        }
+    }
+}
@@ -3,45 +3,72 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
+//! Events sent by Streamduck daemon
 
+use futures::Stream;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
+use tokio::sync::{broadcast, watch};
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
 use crate::api::Device;
 use crate::base::NamespacedDeviceIdentifier;
 use crate::ClientEvent;
+use crate::connection::ConnectionState;
+use crate::version::ConnectionInfo;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
+/// Raw event as it was sent by the daemon
 pub struct SocketEvent {
+    /// Plugin that sent the event
     pub plugin_name: String,
+    /// Name of the event
     pub event_name: String,
+    /// Data of the event
     pub data: Option<Value>
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "PascalCase")]
+/// Error sent by the daemon
 pub struct SocketError {
+    /// Error message
     pub error: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+/// Event received from the daemon or the client itself
 pub enum StreamduckEvent {
+    /// Client connected to the daemon
     #[serde(skip)]
     ClientConnected,
+    /// Client lost connection to the daemon
     #[serde(skip)]
     ClientDisconnected,
+    /// Daemon sent an error that wasn't related to any request
     #[serde(skip)]
     SocketError(String),
+    /// Client checked socket version of the daemon after connecting
+    #[serde(skip)]
+    VersionChecked(ConnectionInfo),
+    /// Subscriber fell behind and missed this many events, only sent with [LagPolicy::Notify]
+    #[serde(skip)]
+    Lagged(u64),
 
+    /// Device got connected
     #[serde(rename = "Core, Device Connected")]
     DeviceConnected(Device),
+    /// Device got disconnected
     #[serde(rename = "Core, Device Disconnected")]
     DeviceDisconnected(NamespacedDeviceIdentifier),
+    /// New device was found
     #[serde(rename = "Core, Device Appeared")]
     DeviceAppeared(Device),
+    /// Device is no longer available
     #[serde(rename = "Core, Device Disappeared")]
     DeviceDisappeared(NamespacedDeviceIdentifier),
 
+    /// Any other event that the client doesn't know about
     Other(SocketEvent)
 }
 
@@ -58,15 +85,92 @@ impl From<ClientEvent> for StreamduckEvent {
                 );
                 let json_value = Value::Object(json_map);
 
-                match serde_json::from_value::<StreamduckEvent>(json_value) {
-                    Ok(event) => event,
-                    Err(err) => {
-                        println!("Failed to parse event: {}", err);
-                        StreamduckEvent::Other(value)
-                    }
-                }
+                // Plugin events and core events with unexpected data are passed on as they are
+                serde_json::from_value::<StreamduckEvent>(json_value)
+                    .unwrap_or(StreamduckEvent::Other(value))
             }
             ClientEvent::Error(error) => StreamduckEvent::SocketError(error.error)
         }
     }
+}
+
+impl StreamduckEvent {
+    /// Device the event is about, if it's one of device events
+    pub fn device(&self) -> Option<&NamespacedDeviceIdentifier> {
+        match self {
+            StreamduckEvent::DeviceConnected(device) |
+            StreamduckEvent::DeviceAppeared(device) => Some(&device.identifier),
+            StreamduckEvent::DeviceDisconnected(identifier) |
+            StreamduckEvent::DeviceDisappeared(identifier) => Some(identifier),
+            _ => None
+        }
+    }
+}
+
+/// Decides what subscribers do when they fall behind and miss events
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum LagPolicy {
+    /// Silently skip to the oldest event that is still available
+    #[default]
+    Skip,
+    /// Produce [StreamduckEvent::Lagged] with amount of missed events, then continue
+    Notify
+}
+
+/// Independent receiver of events, created with [Streamduck::subscribe](crate::Streamduck::subscribe)
+pub struct EventSubscriber {
+    receiver: broadcast::Receiver<StreamduckEvent>,
+    state: watch::Receiver<ConnectionState>,
+    lag_policy: LagPolicy
+}
+
+impl EventSubscriber {
+    pub(crate) fn new(receiver: broadcast::Receiver<StreamduckEvent>, state: watch::Receiver<ConnectionState>, lag_policy: LagPolicy) -> Self {
+        Self {
+            receiver,
+            state,
+            lag_policy
+        }
+    }
+
+    /// Waits for next event, returns `None` once the client is closed and events that were already sent are received
+    pub async fn recv(&mut self) -> Option<StreamduckEvent> {
+        loop {
+            let result = tokio::select! {
+                biased;
+                result = self.receiver.recv() => result,
+                // Sender is kept by the client itself, so the channel never closes on its own
+                _ = self.state.wait_for(ConnectionState::is_closed) => return None
+            };
+
+            match result {
+                Ok(event) => return Some(event),
+                Err(RecvError::Lagged(count)) => if self.lag_policy == LagPolicy::Notify {
+                    return Some(StreamduckEvent::Lagged(count))
+                },
+                Err(RecvError::Closed) => return None
+            }
+        }
+    }
+
+    /// Returns next event if there's one available, without waiting
+    pub fn try_recv(&mut self) -> Option<StreamduckEvent> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(event) => return Some(event),
+                Err(TryRecvError::Lagged(count)) => if self.lag_policy == LagPolicy::Notify {
+                    return Some(StreamduckEvent::Lagged(count))
+                },
+                Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => return None
+            }
+        }
+    }
+
+    /// Turns the subscriber into a [Stream] of events, which ends when the client is closed
+    pub fn into_stream(self) -> impl Stream<Item = StreamduckEvent> + Send + Unpin + 'static {
+        Box::pin(futures::stream::unfold(self, |mut subscriber| async move {
+            let event = subscriber.recv().await?;
+            Some((event, subscriber))
+        }))
+    }
 }
\ No newline at end of file
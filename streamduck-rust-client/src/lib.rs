@@ -4,51 +4,166 @@
  * file, You can obtain one at http://mozilla.org/MPL/2.0/.
  */
 #![warn(missing_docs)]
+//! Rust client for talking to Streamduck daemon over its websocket API
+
+// Lets derive macros refer to this crate by name from within the crate
+extern crate self as streamduck_rust_client;
 
 pub mod api;
 pub(crate) mod message;
 pub mod event;
 pub mod base;
+pub mod error;
+pub mod version;
+pub mod connection;
+pub mod registry;
+pub mod device;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "previews")]
+pub mod preview;
+
+// Lets the derive macro refer to serde without relying on it being a dependency of the crate using it
+#[doc(hidden)]
+pub use serde as __serde;
 
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 use url::Url;
-use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use ezsockets::{Client, ClientConfig, Error};
+use futures::FutureExt;
+use ezsockets::{Client, ClientConfig, CloseFrame, Error, WSError};
 use ezsockets::client::ClientCloseMode;
 use rand::distributions::Alphanumeric;
 use rand::Rng;
-use serde::{Serialize};
-use serde::de::DeserializeOwned;
+use serde_json::Value;
 use thiserror::Error;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{broadcast, watch, Mutex, Notify};
 use tokio::sync::oneshot;
-use crate::api::{ConnectDevice, CoreVersion, Device, GetDeviceInputs, GetDeviceItems, GetDeviceScreenStack, Input, ListDevices, PartialScreenItem, PopScreen, PushNewEmptyScreen, SetDeviceAutoconnect, StreamduckRequest};
-use crate::base::NamespacedDeviceIdentifier;
-use crate::event::{SocketError, SocketEvent, StreamduckEvent};
+use tokio::time::timeout;
+use crate::api::{ConnectDevice, CoreVersion, Device, GetDeviceInputs, GetDeviceItems, GetDeviceScreenStack, GetItemProperties, Input, ItemProperties, ListDevices, PartialScreenItem, PopScreen, PushNewEmptyScreen, RawRequest, SetDeviceAutoconnect, SetItemProperties, StreamduckRequest};
+use crate::base::{NamespacedDeviceIdentifier, NamespacedName};
+use crate::connection::{ConnectionState, ReconnectConfig};
+use crate::device::DeviceHandle;
+use crate::error::{Result, StreamduckError};
+use crate::event::{EventSubscriber, LagPolicy, SocketError, SocketEvent, StreamduckEvent};
 use crate::message::SocketMessage;
+use crate::version::{Compatibility, ConnectionInfo};
+
+/// Default address of Streamduck daemon's websocket
+pub const DEFAULT_URL: &str = "ws://127.0.0.1:42131";
+
+/// Default time to wait for a response before giving up on a request
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default amount of events buffered for each subscriber
+pub const DEFAULT_EVENT_CAPACITY: usize = 256;
 
+/// Client connection to Streamduck daemon
+///
+/// Cloning is cheap, all clones share the same connection
+#[derive(Clone)]
 pub struct Streamduck {
     client: Client<ClientHandler>,
-    event_receiver: Mutex<mpsc::Receiver<ClientEvent>>
+    event_sender: broadcast::Sender<StreamduckEvent>,
+    event_receiver: Arc<Mutex<EventSubscriber>>,
+    lag_policy: LagPolicy,
+    request_timeout: Option<Duration>,
+    connection_info: watch::Receiver<Option<ConnectionInfo>>,
+    connection: Arc<ConnectionControl>,
+    url: Url
+}
+
+/// Configuration used when connecting to Streamduck daemon
+#[derive(Debug, Clone)]
+pub struct StreamduckConfig {
+    /// Websocket URL of the daemon
+    pub url: String,
+    /// How long requests wait for a response, `None` waits forever
+    pub request_timeout: Option<Duration>,
+    /// What happens to requests that were in-flight when connection got lost
+    pub replay_policy: ReplayPolicy,
+    /// How many events are buffered for each subscriber before it starts lagging behind
+    pub event_capacity: usize,
+    /// What subscribers do when they lag behind
+    pub lag_policy: LagPolicy,
+    /// How the client reconnects
+    pub reconnect: ReconnectConfig
+}
+
+/// Decides what happens to in-flight requests when connection to the daemon is lost
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum ReplayPolicy {
+    /// All in-flight requests fail with [StreamduckError::ConnectionLost]
+    #[default]
+    FailAll,
+    /// Idempotent requests are sent again after reconnecting, the rest fail
+    ReplayIdempotent
+}
+
+impl Default for StreamduckConfig {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_URL.to_string(),
+            request_timeout: Some(DEFAULT_REQUEST_TIMEOUT),
+            replay_policy: Default::default(),
+            event_capacity: DEFAULT_EVENT_CAPACITY,
+            lag_policy: Default::default(),
+            reconnect: Default::default()
+        }
+    }
 }
 
 struct ClientHandler {
     handle: Client<Self>,
-    active_requests: HashMap<String, oneshot::Sender<SocketMessage>>,
-    event_sender: mpsc::Sender<ClientEvent>
+    active_requests: HashMap<String, ActiveRequest>,
+    replay_queue: Vec<ActiveRequest>,
+    replay_policy: ReplayPolicy,
+    event_sender: broadcast::Sender<StreamduckEvent>,
+    reconnect: ReconnectConfig,
+    failed_attempts: usize,
+    connection: Arc<ConnectionControl>
+}
+
+/// Connection state shared between the client and its handler
+struct ConnectionControl {
+    state: watch::Sender<ConnectionState>,
+    closing: AtomicBool,
+    close_notify: Notify,
+    retry_notify: Notify
+}
+
+impl ConnectionControl {
+    fn is_closing(&self) -> bool {
+        self.closing.load(Ordering::SeqCst)
+    }
+}
+
+type ResponseSender = oneshot::Sender<Result<SocketMessage>>;
+
+struct ActiveRequest {
+    message: SocketMessage,
+    idempotent: bool,
+    sender: ResponseSender
 }
 
+/// Errors that can happen while handling messages from the daemon
 #[derive(Error, Debug)]
 pub enum ClientHandlerError {
+    /// Response didn't have a request ID
     #[error("Message didn't have Request ID")]
-    MissingRequestID,
-    #[error("Message for unknown sender")]
-    MissingSender,
-    #[error("Message didn't contain any data")]
-    EmptyData,
-    #[error("Error received from request: {0}")]
-    RequestError(String)
+    MissingRequestID
+}
+
+pub(crate) enum ClientCall {
+    Request {
+        message: SocketMessage,
+        idempotent: bool,
+        sender: ResponseSender
+    },
+    Cancel(String)
 }
 
 pub(crate) enum ClientEvent {
@@ -60,7 +175,7 @@ pub(crate) enum ClientEvent {
 
 #[async_trait]
 impl ezsockets::ClientExt for ClientHandler {
-    type Call = (SocketMessage, oneshot::Sender<SocketMessage>);
+    type Call = ClientCall;
 
     async fn on_text(&mut self, text: String) -> std::result::Result<(), Error> {
         // Request
@@ -69,21 +184,20 @@ impl ezsockets::ClientExt for ClientHandler {
                 return Err(Box::new(ClientHandlerError::MissingRequestID))
             };
 
-            let Some(sender) = self.active_requests.remove(request_id) else {
-                return Err(Box::new(ClientHandlerError::MissingSender))
-            };
-
-            sender.send(message).ok();
+            // Response might arrive after the request was cancelled or timed out
+            if let Some(request) = self.active_requests.remove(request_id) {
+                request.sender.send(Ok(message)).ok();
+            }
         }
 
         // Event
         if let Ok(event) = serde_json::from_str::<SocketEvent>(&text) {
-            self.event_sender.send(ClientEvent::Event(event)).await.ok();
+            self.send_event(ClientEvent::Event(event));
         }
 
         // Error
         if let Ok(error) = serde_json::from_str::<SocketError>(&text) {
-            self.event_sender.send(ClientEvent::Error(error)).await.ok();
+            self.send_event(ClientEvent::Error(error));
         }
 
         Ok(())
@@ -94,139 +208,504 @@ impl ezsockets::ClientExt for ClientHandler {
     }
 
     async fn on_call(&mut self, call: Self::Call) -> std::result::Result<(), Error> {
-        if let Some(request_id) = &call.0.request_id {
-            self.active_requests.insert(request_id.to_string(), call.1);
+        match call {
+            ClientCall::Request { message, idempotent, sender } => {
+                // Caller gave up while the call was queued, likely timed out while disconnected
+                if sender.is_closed() {
+                    return Ok(());
+                }
+
+                self.send_active_request(ActiveRequest {
+                    message,
+                    idempotent,
+                    sender,
+                })?;
+            }
+            ClientCall::Cancel(request_id) => {
+                self.active_requests.remove(&request_id);
+            }
         }
 
-        self.handle.text(serde_json::to_string(&call.0)?)?;
         Ok(())
     }
 
     async fn on_connect(&mut self) -> std::result::Result<(), Error> {
-        let _ = self.event_sender.send(ClientEvent::Connected).await;
+        self.failed_attempts = 0;
+        self.connection.state.send_replace(ConnectionState::Connected);
+
+        // Retry that came in during a successful attempt shouldn't skip the delay after the next disconnect
+        self.connection.retry_notify.notified().now_or_never();
+
+        for request in std::mem::take(&mut self.replay_queue) {
+            // Nobody is waiting for it anymore
+            if request.sender.is_closed() {
+                continue;
+            }
+
+            self.send_active_request(request)?;
+        }
+
+        self.send_event(ClientEvent::Connected);
         Ok(())
     }
 
+    async fn on_connect_fail(&mut self, error: WSError) -> std::result::Result<ClientCloseMode, Error> {
+        self.failed_attempts += 1;
+
+        if self.connection.is_closing() {
+            return Ok(self.close(None));
+        }
+
+        if self.reconnect.max_attempts.is_some_and(|max| self.failed_attempts >= max) {
+            return Ok(self.close(Some(format!("Gave up after {} failed attempts: {error}", self.failed_attempts))));
+        }
+
+        self.connection.state.send_replace(ConnectionState::Reconnecting {
+            attempt: self.failed_attempts + 1,
+            last_error: Some(error.to_string())
+        });
+
+        let delay = self.reconnect.delay(self.failed_attempts);
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = self.connection.close_notify.notified() => {}
+            _ = self.connection.retry_notify.notified() => {}
+        }
+
+        if self.connection.is_closing() {
+            return Ok(self.close(None));
+        }
+
+        Ok(ClientCloseMode::Reconnect)
+    }
+
+    async fn on_close(&mut self, _frame: Option<CloseFrame>) -> std::result::Result<ClientCloseMode, Error> {
+        self.on_disconnect().await
+    }
+
     async fn on_disconnect(&mut self) -> std::result::Result<ClientCloseMode, Error> {
-        let _ = self.event_sender.send(ClientEvent::Disconnected).await;
+        self.fail_active_requests();
+
+        self.send_event(ClientEvent::Disconnected);
+
+        if self.connection.is_closing() {
+            return Ok(self.close(None));
+        }
+
+        self.connection.state.send_replace(ConnectionState::Reconnecting {
+            attempt: 1,
+            last_error: None
+        });
+
         Ok(ClientCloseMode::Reconnect)
     }
 }
 
+impl ClientHandler {
+    fn close(&mut self, reason: Option<String>) -> ClientCloseMode {
+        self.connection.state.send_replace(ConnectionState::Closed {
+            reason
+        });
+
+        ClientCloseMode::Close
+    }
+
+    fn send_event(&self, event: ClientEvent) {
+        // Only fails if there's no subscribers
+        self.event_sender.send(StreamduckEvent::from(event)).ok();
+    }
+
+    fn send_active_request(&mut self, request: ActiveRequest) -> std::result::Result<(), Error> {
+        let text = serde_json::to_string(&request.message)?;
+
+        if let Some(request_id) = &request.message.request_id {
+            self.active_requests.insert(request_id.to_string(), request);
+        }
+
+        self.handle.text(text)?;
+        Ok(())
+    }
+
+    /// Fails requests that won't get a response anymore, or keeps them for replay
+    fn fail_active_requests(&mut self) {
+        for (_, request) in self.active_requests.drain() {
+            if request.idempotent && self.replay_policy == ReplayPolicy::ReplayIdempotent {
+                self.replay_queue.push(request);
+            } else {
+                request.sender.send(Err(StreamduckError::ConnectionLost)).ok();
+            }
+        }
+    }
+}
+
+/// Removes the request from active requests if the future waiting for it gets dropped
+struct PendingRequest<'a> {
+    client: &'a Client<ClientHandler>,
+    request_id: Option<String>
+}
+
+impl PendingRequest<'_> {
+    fn finish(mut self) {
+        self.request_id = None;
+    }
+}
+
+impl Drop for PendingRequest<'_> {
+    fn drop(&mut self) {
+        if let Some(request_id) = self.request_id.take() {
+            self.client.call(ClientCall::Cancel(request_id)).ok();
+        }
+    }
+}
+
 impl Streamduck {
+    /// Connects to Streamduck daemon at provided URL, or at [DEFAULT_URL] if none was provided
     pub async fn new(url: Option<&str>) -> Result<Streamduck> {
-        let url = url.unwrap_or("ws://127.0.0.1:42131");
+        Self::with_config(StreamduckConfig {
+            url: url.unwrap_or(DEFAULT_URL).to_string(),
+            ..Default::default()
+        }).await
+    }
+
+    /// Connects to Streamduck daemon using provided config
+    pub async fn with_config(config: StreamduckConfig) -> Result<Streamduck> {
+        let url = Url::parse(&config.url)?;
+        let request_timeout = config.request_timeout;
+        let replay_policy = config.replay_policy;
+        let lag_policy = config.lag_policy;
+        let event_capacity = config.event_capacity.max(1);
+        let reconnect = config.reconnect;
+
+        // Delays between attempts are handled by the client handler
+        let config = ClientConfig::new(url.clone())
+            .reconnect_interval(Duration::ZERO);
+
+        let connection = Arc::new(ConnectionControl {
+            state: watch::channel(ConnectionState::Connecting).0,
+            closing: AtomicBool::new(false),
+            close_notify: Notify::new(),
+            retry_notify: Notify::new()
+        });
+        let handler_connection = connection.clone();
 
-        let url = Url::parse(url)?;
-        let config = ClientConfig::new(url);
+        let (tx, rx) = broadcast::channel::<StreamduckEvent>(event_capacity);
+        let event_sender = tx.clone();
 
-        let (tx, rx) = mpsc::channel::<ClientEvent>(50);
+        // Subscribing before connecting, so the first connection isn't missed
+        let version_events = EventSubscriber::new(tx.subscribe(), connection.state.subscribe(), LagPolicy::Skip);
+        let (info_sender, connection_info) = watch::channel(None);
 
         let (handle, future) = ezsockets::connect(
-            |handle| ClientHandler {
+            move |handle| ClientHandler {
                 handle,
                 active_requests: Default::default(),
-                event_sender: tx
+                replay_queue: vec![],
+                replay_policy,
+                event_sender: tx,
+                reconnect,
+                failed_attempts: 0,
+                connection: handler_connection
             },
             config
         ).await;
 
+        let actor_connection = connection.clone();
         tokio::spawn(async move {
-            future.await.unwrap();
+            let result = future.await;
+
+            // Handler might have already reported why it closed
+            actor_connection.state.send_if_modified(|state| {
+                if state.is_closed() {
+                    return false;
+                }
+
+                *state = ConnectionState::Closed {
+                    reason: result.err().map(|error| error.to_string())
+                };
+                true
+            });
         });
 
-        Ok(Streamduck {
+        let streamduck = Streamduck {
             client: handle,
-            event_receiver: Mutex::new(rx)
-        })
+            event_sender,
+            event_receiver: Arc::new(Mutex::new(EventSubscriber::new(rx, connection.state.subscribe(), lag_policy))),
+            lag_policy,
+            request_timeout,
+            connection_info,
+            connection,
+            url
+        };
+
+        tokio::spawn(check_version(streamduck.clone(), version_events, info_sender));
+
+        Ok(streamduck)
+    }
+
+    /// Current state of the connection
+    pub fn connection_state(&self) -> ConnectionState {
+        self.connection.state.borrow().clone()
+    }
+
+    /// Receiver that gets notified every time connection state changes
+    pub fn watch_connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.connection.state.subscribe()
+    }
+
+    /// Makes the client try to reconnect right away, instead of waiting for the delay to pass
+    ///
+    /// If an attempt is in progress, the delay after it is skipped.
+    /// Returns `false` if there's nothing to retry, because the client is connected or closed
+    pub fn retry(&self) -> bool {
+        let state = self.connection_state();
+
+        if state.is_connected() || state.is_closed() {
+            return false;
+        }
+
+        self.connection.retry_notify.notify_one();
+        true
+    }
+
+    /// URL of the daemon the client connects to
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Closes the connection for good, all clones of this client get closed too
+    ///
+    /// Pending requests fail and new requests fail immediately
+    pub fn close(&self) {
+        self.connection.closing.store(true, Ordering::SeqCst);
+        self.connection.close_notify.notify_one();
+        self.client.close(None).ok();
+    }
+
+    /// Information about current connection, `None` if not connected or socket version wasn't checked yet
+    pub fn connection_info(&self) -> Option<ConnectionInfo> {
+        self.connection_info.borrow().clone()
+    }
+
+    /// Receiver that gets notified every time connection info changes
+    pub fn watch_connection_info(&self) -> watch::Receiver<Option<ConnectionInfo>> {
+        self.connection_info.clone()
     }
 
-    async fn do_request<S>(&self, value: S) -> Result<SocketMessage> where S : Serialize + StreamduckRequest {
+    /// Returns a client sharing the same connection, but using a different request timeout
+    ///
+    /// `None` makes requests wait for a response forever
+    pub fn with_timeout(&self, timeout: Option<Duration>) -> Streamduck {
+        Streamduck {
+            request_timeout: timeout,
+            ..self.clone()
+        }
+    }
+
+    /// Request timeout currently used by this client
+    pub fn request_timeout(&self) -> Option<Duration> {
+        self.request_timeout
+    }
+
+    async fn do_request<S>(&self, value: S) -> Result<SocketMessage> where S : StreamduckRequest {
         let id: String = rand::thread_rng()
             .sample_iter(Alphanumeric)
             .take(12)
             .map(char::from)
             .collect();
 
-        let (tx, rx) = oneshot::channel::<SocketMessage>();
-        let packet = SocketMessage::new_from(value, &id)?;
-        self.client.call((packet, tx))?;
+        let idempotent = value.idempotent();
+
+        let (tx, rx) = oneshot::channel();
+        let packet = SocketMessage::new_from(value, &id)
+            .map_err(StreamduckError::Serialize)?;
+        self.client.call(ClientCall::Request {
+            message: packet,
+            idempotent,
+            sender: tx
+        }).map_err(|_| StreamduckError::ClientClosed)?;
+
+        let pending = PendingRequest {
+            client: &self.client,
+            request_id: Some(id)
+        };
+
+        let response = match self.request_timeout {
+            Some(duration) => timeout(duration, rx).await
+                .map_err(|_| StreamduckError::Timeout(duration))?,
+            None => rx.await
+        }.map_err(|_| StreamduckError::ClientClosed)??;
+
+        pending.finish();
+        Ok(response)
+    }
+
+    /// Sends the request and waits for its response
+    ///
+    /// Can be used with requests defined outside of this crate, see [StreamduckRequest]
+    pub async fn call<R>(&self, request: R) -> Result<R::Response> where R : StreamduckRequest {
+        let data = self.send_request_raw(request).await?;
+
+        serde_json::from_value(data.clone())
+            .map_err(|source| StreamduckError::Deserialize {
+                source,
+                payload: data
+            })
+    }
 
-        Ok(rx.await?)
+    /// Sends untyped request with provided name and data, returns untyped response
+    ///
+    /// Useful for calling requests registered by plugins
+    pub async fn call_raw(&self, name: NamespacedName, data: Value) -> Result<Value> {
+        self.call(RawRequest {
+            name,
+            data
+        }).await
     }
 
-    async fn send_request<S, R>(&self, value: S) -> Result<R> where S : Serialize + StreamduckRequest, R : DeserializeOwned {
+    /// Sends the request and checks if the response was an error
+    async fn send_request_raw<S>(&self, value: S) -> Result<Value> where S : StreamduckRequest {
         let message = self.do_request(value).await?;
 
-        if message.data.is_object() && message.data.as_object().unwrap().contains_key("Error") {
-            let error = serde_json::from_value::<SocketError>(message.data)?;
-            return Err(anyhow!(ClientHandlerError::RequestError(error.error)))
-        }
+        if let Some(error) = message.data.as_object().and_then(|object| object.get("Error")) {
+            let Some(error) = error.as_str() else {
+                return Err(StreamduckError::Protocol {
+                    reason: "Error wasn't a string".to_string(),
+                    payload: message.data
+                })
+            };
 
-        Ok(serde_json::from_value(message.data)?)
-    }
+            return Err(StreamduckError::Daemon {
+                message: error.to_string(),
+                payload: message.data
+            })
+        }
 
-    async fn send_request_empty_response<S>(&self, value: S) -> Result<()> where S : Serialize + StreamduckRequest {
-        self.do_request(value).await?;
-        Ok(())
+        Ok(message.data)
     }
 
+    /// Waits for next event from the daemon, returns `None` if client was closed
+    ///
+    /// All callers of this method share a single receiver, so each event is only seen by one of them.
+    /// Use [Streamduck::subscribe] to get an independent stream of events
     pub async fn wait_for_event(&self) -> Option<StreamduckEvent> {
         let mut receiver = self.event_receiver.lock().await;
-        let socket_event = receiver.recv().await;
+        receiver.recv().await
+    }
+
+    /// Creates a new subscriber that receives every event sent after this call
+    pub fn subscribe(&self) -> EventSubscriber {
+        EventSubscriber::new(self.event_sender.subscribe(), self.watch_connection_state(), self.lag_policy)
+    }
 
-        Some(StreamduckEvent::from(socket_event?))
+    /// Creates handle for working with a single device
+    pub fn device(&self, identifier: NamespacedDeviceIdentifier) -> DeviceHandle {
+        DeviceHandle::new(self.clone(), identifier)
     }
 
+    /// Retrieves socket API version of the daemon
     pub async fn core_version(&self) -> Result<String> {
-        Ok(self.send_request(CoreVersion).await?)
+        self.call(CoreVersion).await
     }
 
+    /// Lists all devices known to the daemon
     pub async fn list_devices(&self) -> Result<Vec<Device>> {
-        Ok(self.send_request(ListDevices).await?)
+        self.call(ListDevices).await
     }
 
+    /// Sets if daemon should automatically connect to the device
     pub async fn set_device_autoconnect(&self, identifier: NamespacedDeviceIdentifier, autoconnect: bool) -> Result<()> {
-        Ok(self.send_request_empty_response(SetDeviceAutoconnect {
+        self.call(SetDeviceAutoconnect {
             identifier,
             autoconnect,
-        }).await?)
+        }).await?;
+        Ok(())
     }
 
+    /// Retrieves input layout of the device
     pub async fn get_device_inputs(&self, identifier: NamespacedDeviceIdentifier) -> Result<Vec<Input>> {
-        Ok(self.send_request(GetDeviceInputs {
+        self.call(GetDeviceInputs {
             identifier,
-        }).await?)
+        }).await
     }
 
+    /// Connects to the device, returns if connection was successful
     pub async fn connect_device(&self, identifier: NamespacedDeviceIdentifier) -> Result<bool> {
-        Ok(self.send_request(ConnectDevice {
+        self.call(ConnectDevice {
             identifier
-        }).await?)
+        }).await
     }
     
+    /// Retrieves screen items of the device's current screen, optionally with previews
     pub async fn get_device_items(&self, identifier: NamespacedDeviceIdentifier, previews: Option<bool>) -> Result<Vec<Option<PartialScreenItem>>> {
-        Ok(self.send_request(GetDeviceItems {
+        self.call(GetDeviceItems {
             identifier,
             get_previews: previews.unwrap_or(false),
-        }).await?)
+        }).await
     }
     
+    /// Retrieves names of screens currently in device's screen stack
     pub async fn get_device_screen_stack(&self, identifier: NamespacedDeviceIdentifier) -> Result<Vec<String>> {
-        Ok(self.send_request(GetDeviceScreenStack {
+        self.call(GetDeviceScreenStack {
             identifier
-        }).await?)
+        }).await
     }
     
+    /// Pops top screen from device's screen stack, returns if anything was popped
     pub async fn pop_screen(&self, identifier: NamespacedDeviceIdentifier) -> Result<bool> {
-        Ok(self.send_request(PopScreen {
+        self.call(PopScreen {
             identifier
-        }).await?)
+        }).await
     }
     
+    /// Pushes a new empty screen onto device's screen stack
     pub async fn push_new_empty_screen(&self, identifier: NamespacedDeviceIdentifier) -> Result<()> {
-        Ok(self.send_request_empty_response(PushNewEmptyScreen {
+        self.call(PushNewEmptyScreen {
             identifier
-        }).await?)
+        }).await?;
+        Ok(())
+    }
+
+    /// Retrieves properties of the item assigned to the input, `None` if nothing is assigned
+    pub async fn get_item_properties(&self, identifier: NamespacedDeviceIdentifier, input: usize) -> Result<Option<ItemProperties>> {
+        self.call(GetItemProperties {
+            identifier,
+            input
+        }).await
     }
-}
\ No newline at end of file
+
+    /// Replaces properties of the item assigned to the input
+    pub async fn set_item_properties(&self, identifier: NamespacedDeviceIdentifier, input: usize, properties: ItemProperties) -> Result<()> {
+        self.call(SetItemProperties {
+            identifier,
+            input,
+            properties
+        }).await?;
+        Ok(())
+    }
+}
+
+/// Checks socket version every time the client connects
+async fn check_version(streamduck: Streamduck, mut events: EventSubscriber, info_sender: watch::Sender<Option<ConnectionInfo>>) {
+    while let Some(event) = events.recv().await {
+        match event {
+            StreamduckEvent::ClientConnected => {
+                let info = match streamduck.core_version().await {
+                    Ok(version) => ConnectionInfo::from_version(&version),
+                    Err(error) => ConnectionInfo {
+                        socket_version: None,
+                        compatibility: Compatibility::Unknown(error.to_string())
+                    }
+                };
+
+                info_sender.send_replace(Some(info.clone()));
+                streamduck.event_sender.send(StreamduckEvent::VersionChecked(info)).ok();
+            }
+            StreamduckEvent::ClientDisconnected => {
+                info_sender.send_replace(None);
+            }
+            _ => {}
+        }
+    }
+
+    info_sender.send_replace(None);
+}
@@ -19,7 +19,7 @@ pub struct SocketMessage {
 
 impl SocketMessage {
     pub fn new_from<T>(value: T, request_id: &str) -> Result<SocketMessage, serde_json::Error>
-    where T : StreamduckRequest + Serialize {
+    where T : StreamduckRequest {
         Ok(Self {
             name: value.name(),
             data: serde_json::to_value(value)?,
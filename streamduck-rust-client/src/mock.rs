@@ -0,0 +1,266 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+//! In-process mock of Streamduck daemon, meant for testing the client over loopback
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::accept_async;
+use tokio_tungstenite::tungstenite::Message;
+use crate::api::{ConnectDevice, CoreVersion, GetDeviceInputs, GetDeviceItems, GetDeviceScreenStack, GetItemProperties, ListDevices, PopScreen, PushNewEmptyScreen, SetDeviceAutoconnect, SetItemProperties, StreamduckRequest};
+use crate::base::{NamespacedDeviceIdentifier, NamespacedName};
+use crate::event::{SocketError, SocketEvent};
+use crate::message::SocketMessage;
+
+/// Socket version reported by the mock daemon by default
+pub const MOCK_SOCKET_VERSION: &str = "0.1";
+
+/// How the mock daemon responds to a request
+#[derive(Debug, Clone)]
+pub enum MockResponse {
+    /// Respond with data
+    Data(Value),
+    /// Respond with an error
+    Error(String),
+    /// Wait before responding
+    Delayed(Duration, Box<MockResponse>),
+    /// Never respond
+    Silent
+}
+
+impl MockResponse {
+    /// Creates data response out of anything serializable
+    pub fn data<T: Serialize>(value: T) -> MockResponse {
+        MockResponse::Data(serde_json::to_value(value).expect("Failed to serialize mock response"))
+    }
+}
+
+type MockHandler = Box<dyn Fn(&Value) -> MockResponse + Send + Sync>;
+
+/// Request that was received by the mock daemon
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    /// Name of the request
+    pub name: NamespacedName,
+    /// Data of the request
+    pub data: Value
+}
+
+#[derive(Clone)]
+enum Outgoing {
+    Text(String),
+    Disconnect
+}
+
+struct Shared {
+    handlers: Mutex<HashMap<NamespacedName, MockHandler>>,
+    requests: Mutex<Vec<MockRequest>>,
+    outgoing: broadcast::Sender<Outgoing>
+}
+
+/// Websocket server pretending to be Streamduck daemon
+///
+/// Responds to all core requests with empty but valid data by default,
+/// responses can be overridden with [MockDaemon::respond] or [MockDaemon::respond_with]
+pub struct MockDaemon {
+    address: SocketAddr,
+    shared: Arc<Shared>,
+    server: JoinHandle<()>
+}
+
+impl MockDaemon {
+    /// Starts the mock daemon on a random loopback port
+    pub async fn start() -> std::io::Result<MockDaemon> {
+        Self::start_on("127.0.0.1:0").await
+    }
+
+    /// Starts the mock daemon on specific address, useful for bringing a daemon back up where the client expects it
+    pub async fn start_on(address: impl ToSocketAddrs) -> std::io::Result<MockDaemon> {
+        let listener = TcpListener::bind(address).await?;
+        let address = listener.local_addr()?;
+
+        let (outgoing, _) = broadcast::channel(64);
+
+        let shared = Arc::new(Shared {
+            handlers: Default::default(),
+            requests: Default::default(),
+            outgoing
+        });
+
+        let daemon = MockDaemon {
+            address,
+            shared: shared.clone(),
+            server: tokio::spawn(serve(listener, shared))
+        };
+
+        daemon.respond(CoreVersion.name(), MockResponse::data(MOCK_SOCKET_VERSION));
+        daemon.respond(ListDevices.name(), MockResponse::data(json!([])));
+
+        let identifier = NamespacedDeviceIdentifier::default();
+        daemon.respond(SetDeviceAutoconnect { identifier: identifier.clone(), autoconnect: false }.name(), MockResponse::Data(Value::Null));
+        daemon.respond(GetDeviceInputs { identifier: identifier.clone() }.name(), MockResponse::data(json!([])));
+        daemon.respond(ConnectDevice { identifier: identifier.clone() }.name(), MockResponse::data(true));
+        daemon.respond(GetDeviceItems { identifier: identifier.clone(), get_previews: false }.name(), MockResponse::data(json!([])));
+        daemon.respond(GetDeviceScreenStack { identifier: identifier.clone() }.name(), MockResponse::data(json!([])));
+        daemon.respond(PopScreen { identifier: identifier.clone() }.name(), MockResponse::data(true));
+        daemon.respond(PushNewEmptyScreen { identifier: identifier.clone() }.name(), MockResponse::Data(Value::Null));
+        daemon.respond(GetItemProperties { identifier: identifier.clone(), input: 0 }.name(), MockResponse::Data(Value::Null));
+        daemon.respond(SetItemProperties { identifier, input: 0, properties: Default::default() }.name(), MockResponse::Data(Value::Null));
+
+        Ok(daemon)
+    }
+
+    /// Address the mock daemon is listening on
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Websocket URL that clients should connect to
+    pub fn url(&self) -> String {
+        format!("ws://{}", self.address)
+    }
+
+    /// Sets a fixed response for requests with provided name
+    pub fn respond(&self, name: NamespacedName, response: MockResponse) {
+        self.respond_with(name, move |_| response.clone());
+    }
+
+    /// Sets a function that produces responses out of request data for requests with provided name
+    pub fn respond_with<F>(&self, name: NamespacedName, handler: F)
+    where F : Fn(&Value) -> MockResponse + Send + Sync + 'static {
+        self.shared.handlers.lock().unwrap().insert(name, Box::new(handler));
+    }
+
+    /// Requests received so far, in the order they were received
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.shared.requests.lock().unwrap().clone()
+    }
+
+    /// Sends an event to all connected clients
+    pub fn emit_event(&self, plugin_name: &str, event_name: &str, data: Option<Value>) {
+        let event = SocketEvent {
+            plugin_name: plugin_name.to_string(),
+            event_name: event_name.to_string(),
+            data,
+        };
+
+        self.send(serde_json::to_string(&event).unwrap());
+    }
+
+    /// Sends an error that isn't related to any request to all connected clients
+    pub fn emit_error(&self, error: &str) {
+        let error = SocketError {
+            error: error.to_string()
+        };
+
+        self.send(serde_json::to_string(&error).unwrap());
+    }
+
+    /// Closes connections of all connected clients, clients are free to reconnect afterwards
+    pub fn disconnect_clients(&self) {
+        self.shared.outgoing.send(Outgoing::Disconnect).ok();
+    }
+
+    fn send(&self, text: String) {
+        self.shared.outgoing.send(Outgoing::Text(text)).ok();
+    }
+}
+
+impl Drop for MockDaemon {
+    fn drop(&mut self) {
+        self.disconnect_clients();
+        self.server.abort();
+    }
+}
+
+async fn serve(listener: TcpListener, shared: Arc<Shared>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        tokio::spawn(handle_connection(stream, shared.clone()));
+    }
+}
+
+async fn handle_connection(stream: TcpStream, shared: Arc<Shared>) {
+    let Ok(socket) = accept_async(stream).await else {
+        return;
+    };
+
+    let (write, mut read) = socket.split();
+    let write = Arc::new(tokio::sync::Mutex::new(write));
+    let mut outgoing = shared.outgoing.subscribe();
+
+    loop {
+        tokio::select! {
+            message = read.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => continue
+                };
+
+                let Ok(message) = serde_json::from_str::<SocketMessage>(&text) else {
+                    continue;
+                };
+
+                shared.requests.lock().unwrap().push(MockRequest {
+                    name: message.name.clone(),
+                    data: message.data.clone()
+                });
+
+                let response = shared.handlers.lock().unwrap()
+                    .get(&message.name)
+                    .map(|handler| handler(&message.data))
+                    .unwrap_or_else(|| MockResponse::Error(format!("Unknown request: {}", message.name)));
+
+                let write = write.clone();
+                tokio::spawn(async move {
+                    if let Some(data) = resolve_response(response).await {
+                        let reply = SocketMessage {
+                            name: message.name,
+                            data,
+                            request_id: message.request_id
+                        };
+
+                        let text = serde_json::to_string(&reply).unwrap();
+                        write.lock().await.send(Message::Text(text)).await.ok();
+                    }
+                });
+            }
+            outgoing = outgoing.recv() => {
+                match outgoing {
+                    Ok(Outgoing::Text(text)) => {
+                        write.lock().await.send(Message::Text(text)).await.ok();
+                    }
+                    Ok(Outgoing::Disconnect) | Err(broadcast::error::RecvError::Closed) => {
+                        write.lock().await.send(Message::Close(None)).await.ok();
+                        break;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                }
+            }
+        }
+    }
+}
+
+async fn resolve_response(mut response: MockResponse) -> Option<Value> {
+    loop {
+        match response {
+            MockResponse::Data(data) => return Some(data),
+            MockResponse::Error(error) => return Some(json!({ "Error": error })),
+            MockResponse::Delayed(duration, inner) => {
+                tokio::time::sleep(duration).await;
+                response = *inner;
+            }
+            MockResponse::Silent => return None
+        }
+    }
+}
@@ -0,0 +1,165 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+//! Decoding of screen item previews, enabled with `previews` feature
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, OnceLock};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use image::ImageFormat;
+use thiserror::Error;
+use crate::api::PartialScreenItem;
+
+/// How many decoded previews the shared cache keeps
+pub const DEFAULT_PREVIEW_CACHE_CAPACITY: usize = 256;
+
+/// Decoded preview of a screen item
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PreviewImage {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Pixels in RGBA order, row by row
+    pub rgba: Vec<u8>
+}
+
+/// Reasons why preview couldn't be decoded
+#[derive(Error, Debug)]
+pub enum PreviewError {
+    /// Item was retrieved without previews
+    #[error("Item has no preview")]
+    NoPreview,
+    /// Preview isn't valid base64
+    #[error("Preview is not valid base64: {0}")]
+    Base64(#[from] base64::DecodeError),
+    /// Preview isn't a valid JPEG
+    #[error("Preview is not a valid image: {0}")]
+    Image(#[from] image::ImageError)
+}
+
+/// Cache of decoded previews, keyed by hash of the encoded preview
+///
+/// Least recently used previews get evicted once the cache is full
+pub struct PreviewCache {
+    capacity: usize,
+    entries: Mutex<CacheEntries>
+}
+
+#[derive(Default)]
+struct CacheEntries {
+    images: HashMap<u64, Arc<PreviewImage>>,
+    order: VecDeque<u64>
+}
+
+impl PreviewCache {
+    /// Creates new cache that keeps up to `capacity` previews
+    pub fn new(capacity: usize) -> PreviewCache {
+        PreviewCache {
+            capacity: capacity.max(1),
+            entries: Default::default()
+        }
+    }
+
+    /// Cache shared by [PartialScreenItem::decode_preview]
+    pub fn shared() -> &'static PreviewCache {
+        static SHARED: OnceLock<PreviewCache> = OnceLock::new();
+        SHARED.get_or_init(|| PreviewCache::new(DEFAULT_PREVIEW_CACHE_CAPACITY))
+    }
+
+    /// Decodes preview of the item, or returns the cached one if same preview was decoded before
+    pub fn decode(&self, item: &PartialScreenItem) -> Result<Arc<PreviewImage>, PreviewError> {
+        let encoded = item.base64jpg.as_deref().ok_or(PreviewError::NoPreview)?;
+        let key = content_hash(encoded);
+
+        if let Some(image) = self.lock().get(key) {
+            return Ok(image);
+        }
+
+        // Decoding outside of the lock, so other previews don't have to wait
+        let image = Arc::new(decode(encoded)?);
+        self.lock().insert(key, image.clone(), self.capacity);
+
+        Ok(image)
+    }
+
+    /// Amount of cached previews
+    pub fn len(&self) -> usize {
+        self.lock().images.len()
+    }
+
+    /// If there's no cached previews
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached previews
+    pub fn clear(&self) {
+        *self.lock() = Default::default();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheEntries> {
+        // Entries are always left consistent, so poisoning can be ignored
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl CacheEntries {
+    fn get(&mut self, key: u64) -> Option<Arc<PreviewImage>> {
+        let image = self.images.get(&key)?.clone();
+        self.touch(key);
+        Some(image)
+    }
+
+    fn insert(&mut self, key: u64, image: Arc<PreviewImage>, capacity: usize) {
+        if self.images.insert(key, image).is_some() {
+            self.touch(key);
+            return;
+        }
+
+        self.order.push_back(key);
+
+        while self.order.len() > capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.images.remove(&oldest);
+            }
+        }
+    }
+
+    fn touch(&mut self, key: u64) {
+        if let Some(position) = self.order.iter().position(|k| *k == key) {
+            self.order.remove(position);
+        }
+
+        self.order.push_back(key);
+    }
+}
+
+impl PartialScreenItem {
+    /// Decodes preview of the item into RGBA pixels, using the shared [PreviewCache]
+    pub fn decode_preview(&self) -> Result<Arc<PreviewImage>, PreviewError> {
+        PreviewCache::shared().decode(self)
+    }
+}
+
+fn content_hash(encoded: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    encoded.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn decode(encoded: &str) -> Result<PreviewImage, PreviewError> {
+    let bytes = STANDARD.decode(encoded.trim())?;
+    let image = image::load_from_memory_with_format(&bytes, ImageFormat::Jpeg)?.into_rgba8();
+
+    Ok(PreviewImage {
+        width: image.width(),
+        height: image.height(),
+        rgba: image.into_raw()
+    })
+}
@@ -0,0 +1,197 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+//! Client-side cache of devices known to the daemon
+
+use std::sync::Arc;
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+use crate::api::Device;
+use crate::base::NamespacedDeviceIdentifier;
+use crate::event::{EventSubscriber, LagPolicy, StreamduckEvent};
+use crate::Streamduck;
+
+/// How many change notifications are buffered for each subscriber
+const CHANGE_CAPACITY: usize = 64;
+
+/// Change that was applied to the registry
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DeviceChange {
+    /// Device list was replaced with a fresh one from the daemon
+    Synced,
+    /// New device was added
+    Added(Device),
+    /// Device was removed
+    Removed(NamespacedDeviceIdentifier),
+    /// Device's state has changed
+    Updated(Device)
+}
+
+/// List of devices that seeds itself from the daemon and is kept up to date by events
+///
+/// Re-syncs after every reconnect and whenever it misses events.
+/// Cloning is cheap, all clones share the same list
+#[derive(Clone)]
+pub struct DeviceRegistry {
+    inner: Arc<RegistryInner>
+}
+
+struct RegistryInner {
+    devices: Arc<watch::Sender<Vec<Device>>>,
+    changes: broadcast::Sender<DeviceChange>,
+    task: JoinHandle<()>
+}
+
+impl Drop for RegistryInner {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl DeviceRegistry {
+    /// Creates registry for the client, syncing with the daemon as soon as the client is connected
+    pub fn new(streamduck: &Streamduck) -> DeviceRegistry {
+        // Subscribing before checking the state, so connect event can't be missed
+        let events = EventSubscriber::new(streamduck.event_sender.subscribe(), streamduck.watch_connection_state(), LagPolicy::Notify);
+
+        let devices = Arc::new(watch::channel(vec![]).0);
+        let (changes, _) = broadcast::channel(CHANGE_CAPACITY);
+
+        let task = tokio::spawn(keep_in_sync(streamduck.clone(), events, devices.clone(), changes.clone()));
+
+        DeviceRegistry {
+            inner: Arc::new(RegistryInner {
+                devices,
+                changes,
+                task
+            })
+        }
+    }
+
+    /// All currently known devices
+    pub fn devices(&self) -> Vec<Device> {
+        self.inner.devices.borrow().clone()
+    }
+
+    /// Device with the identifier, if it's known
+    pub fn device(&self, identifier: &NamespacedDeviceIdentifier) -> Option<Device> {
+        self.inner.devices.borrow().iter()
+            .find(|device| &device.identifier == identifier)
+            .cloned()
+    }
+
+    /// Receiver that gets notified with whole device list every time it changes
+    pub fn watch(&self) -> watch::Receiver<Vec<Device>> {
+        self.inner.devices.subscribe()
+    }
+
+    /// Receiver of individual changes applied to the registry
+    pub fn subscribe(&self) -> broadcast::Receiver<DeviceChange> {
+        self.inner.changes.subscribe()
+    }
+
+    /// Updates autoconnect of a known device, daemon doesn't send an event when it's changed
+    ///
+    /// Meant to be called after [Streamduck::set_device_autoconnect] succeeds
+    pub fn set_autoconnect(&self, identifier: &NamespacedDeviceIdentifier, autoconnect: bool) {
+        let mut change = None;
+
+        self.inner.devices.send_if_modified(|devices| {
+            let Some(device) = devices.iter_mut().find(|d| &d.identifier == identifier && d.autoconnect != autoconnect) else {
+                return false;
+            };
+
+            device.autoconnect = autoconnect;
+            change = Some(DeviceChange::Updated(device.clone()));
+            true
+        });
+
+        if let Some(change) = change {
+            // Only fails if there's no subscribers
+            self.inner.changes.send(change).ok();
+        }
+    }
+}
+
+async fn keep_in_sync(streamduck: Streamduck, mut events: EventSubscriber, devices: Arc<watch::Sender<Vec<Device>>>, changes: broadcast::Sender<DeviceChange>) {
+    if streamduck.connection_state().is_connected() {
+        sync(&streamduck, &devices, &changes).await;
+    }
+
+    while let Some(event) = events.recv().await {
+        match event {
+            StreamduckEvent::ClientConnected | StreamduckEvent::Lagged(_) => {
+                sync(&streamduck, &devices, &changes).await;
+            }
+
+            event => {
+                let mut change = None;
+
+                devices.send_if_modified(|devices| {
+                    change = apply_event(devices, event);
+                    change.is_some()
+                });
+
+                if let Some(change) = change {
+                    // Only fails if there's no subscribers
+                    changes.send(change).ok();
+                }
+            }
+        }
+    }
+}
+
+async fn sync(streamduck: &Streamduck, devices: &watch::Sender<Vec<Device>>, changes: &broadcast::Sender<DeviceChange>) {
+    // Keeping the old list if it fails, next reconnect will try again
+    if let Ok(list) = streamduck.list_devices().await {
+        devices.send_replace(list);
+        changes.send(DeviceChange::Synced).ok();
+    }
+}
+
+/// Applies the event to the device list, returns what changed if anything did
+pub fn apply_event(devices: &mut Vec<Device>, event: StreamduckEvent) -> Option<DeviceChange> {
+    match event {
+        StreamduckEvent::DeviceAppeared(device) => {
+            if let Some(existing) = devices.iter_mut().find(|d| d.identifier == device.identifier) {
+                if *existing == device {
+                    return None;
+                }
+
+                *existing = device.clone();
+                Some(DeviceChange::Updated(device))
+            } else {
+                devices.push(device.clone());
+                Some(DeviceChange::Added(device))
+            }
+        }
+
+        StreamduckEvent::DeviceDisappeared(identifier) => {
+            let count = devices.len();
+            devices.retain(|d| d.identifier != identifier);
+
+            (devices.len() != count).then_some(DeviceChange::Removed(identifier))
+        }
+
+        StreamduckEvent::DeviceConnected(device) => {
+            let device = Device {
+                connected: true,
+                ..device
+            };
+
+            apply_event(devices, StreamduckEvent::DeviceAppeared(device))
+        }
+
+        StreamduckEvent::DeviceDisconnected(identifier) => {
+            let device = devices.iter_mut()
+                .find(|d| d.identifier == identifier && d.connected)?;
+
+            device.connected = false;
+            Some(DeviceChange::Updated(device.clone()))
+        }
+
+        _ => None
+    }
+}
@@ -0,0 +1,106 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+//! Socket API version checks
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Version of daemon's socket API
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct SocketVersion {
+    /// Major version, changes when the API breaks
+    pub major: u32,
+    /// Minor version, changes when API is extended
+    pub minor: u32
+}
+
+impl SocketVersion {
+    /// Creates new socket version
+    pub const fn new(major: u32, minor: u32) -> SocketVersion {
+        SocketVersion {
+            major,
+            minor
+        }
+    }
+}
+
+impl FromStr for SocketVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split('.');
+
+        let mut next_number = || parts.next()
+            .and_then(|part| part.parse::<u32>().ok())
+            .ok_or_else(|| format!("Invalid socket version: {s}"));
+
+        Ok(SocketVersion {
+            major: next_number()?,
+            minor: next_number()?
+        })
+    }
+}
+
+impl Display for SocketVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Oldest socket version the client can talk to
+pub const MIN_SUPPORTED_SOCKET_VERSION: SocketVersion = SocketVersion::new(0, 1);
+
+/// Newest socket version the client can talk to
+pub const MAX_SUPPORTED_SOCKET_VERSION: SocketVersion = SocketVersion::new(0, 1);
+
+/// If the client can talk to the daemon
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Compatibility {
+    /// Daemon's socket version is within supported range
+    Compatible,
+    /// Daemon's socket version is outside of supported range
+    Incompatible,
+    /// Socket version couldn't be retrieved or understood
+    Unknown(String)
+}
+
+/// Information about current connection to the daemon, gathered after connecting
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ConnectionInfo {
+    /// Socket version as reported by the daemon
+    pub socket_version: Option<String>,
+    /// If the client can talk to the daemon
+    pub compatibility: Compatibility
+}
+
+impl ConnectionInfo {
+    /// Checks version reported by the daemon against supported range
+    pub fn from_version(version: &str) -> ConnectionInfo {
+        let compatibility = match version.parse::<SocketVersion>() {
+            Ok(parsed) => if is_supported(parsed) {
+                Compatibility::Compatible
+            } else {
+                Compatibility::Incompatible
+            },
+            Err(error) => Compatibility::Unknown(error)
+        };
+
+        ConnectionInfo {
+            socket_version: Some(version.to_string()),
+            compatibility
+        }
+    }
+
+    /// If the daemon is known to be incompatible
+    pub fn is_incompatible(&self) -> bool {
+        self.compatibility == Compatibility::Incompatible
+    }
+}
+
+/// Checks if the version is within supported range
+pub fn is_supported(version: SocketVersion) -> bool {
+    (MIN_SUPPORTED_SOCKET_VERSION..=MAX_SUPPORTED_SOCKET_VERSION).contains(&version)
+}
@@ -0,0 +1,565 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+
+use std::time::Duration;
+use futures::StreamExt;
+use serde::Serialize;
+use serde_json::json;
+use streamduck_rust_client::api::{CoreVersion, Device, GetDeviceInputs, GetItemProperties, Input, InputIcon, ItemProperties, ListDevices, PopScreen, StreamduckRequest};
+use streamduck_rust_client::base::{DeviceIdentifier, NamespacedDeviceIdentifier, NamespacedName};
+use streamduck_rust_client::event::StreamduckEvent;
+use streamduck_rust_client::mock::{MockDaemon, MockResponse};
+use streamduck_rust_client::error::StreamduckError;
+use streamduck_rust_client::version::Compatibility;
+use streamduck_rust_client::connection::{ConnectionState, ReconnectConfig};
+use streamduck_rust_client::registry::{apply_event, DeviceChange, DeviceRegistry};
+use streamduck_rust_client::{ReplayPolicy, Streamduck, StreamduckConfig};
+
+fn test_device() -> Device {
+    Device {
+        identifier: NamespacedDeviceIdentifier {
+            name: NamespacedName::new("Test Plugin", "Test Driver"),
+            identifier: DeviceIdentifier::new("SERIAL01", "Test Device"),
+        },
+        connected: false,
+        autoconnect: true,
+    }
+}
+
+async fn connect(daemon: &MockDaemon, config: StreamduckConfig) -> Streamduck {
+    let streamduck = Streamduck::with_config(StreamduckConfig {
+        url: daemon.url(),
+        ..config
+    }).await.unwrap();
+
+    wait_for(&streamduck, |event| matches!(event, StreamduckEvent::ClientConnected)).await;
+    streamduck
+}
+
+async fn wait_for(streamduck: &Streamduck, predicate: impl Fn(&StreamduckEvent) -> bool) -> StreamduckEvent {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let event = streamduck.wait_for_event().await.expect("Client was closed");
+            if predicate(&event) {
+                return event;
+            }
+        }
+    }).await.expect("Event didn't arrive in time")
+}
+
+fn is_from_daemon(event: &StreamduckEvent) -> bool {
+    !matches!(event, StreamduckEvent::VersionChecked(_))
+}
+
+#[tokio::test]
+async fn scripted_response_is_returned() {
+    let daemon = MockDaemon::start().await.unwrap();
+    daemon.respond(ListDevices.name(), MockResponse::data(vec![test_device()]));
+
+    let streamduck = connect(&daemon, Default::default()).await;
+
+    assert_eq!(streamduck.list_devices().await.unwrap(), vec![test_device()]);
+    assert_eq!(daemon.requests().last().unwrap().name, ListDevices.name());
+}
+
+#[derive(Serialize, StreamduckRequest)]
+#[serde(rename_all = "PascalCase")]
+#[request(plugin = "Test Plugin", name = "Add Numbers", response = i32)]
+struct AddNumbers {
+    a: i32,
+    b: i32
+}
+
+#[tokio::test]
+async fn custom_request_can_be_called() {
+    let daemon = MockDaemon::start().await.unwrap();
+    daemon.respond_with(NamespacedName::new("Test Plugin", "Add Numbers"), |data| {
+        MockResponse::data(data["A"].as_i64().unwrap() + data["B"].as_i64().unwrap())
+    });
+
+    let streamduck = connect(&daemon, Default::default()).await;
+
+    assert_eq!(streamduck.call(AddNumbers { a: 2, b: 3 }).await.unwrap(), 5);
+    assert!(!AddNumbers { a: 0, b: 0 }.idempotent());
+    assert!(ListDevices.idempotent());
+}
+
+#[tokio::test]
+async fn raw_request_can_be_called() {
+    let daemon = MockDaemon::start().await.unwrap();
+    let name = NamespacedName::new("Test Plugin", "Echo");
+    daemon.respond_with(name.clone(), |data| MockResponse::Data(data.clone()));
+
+    let streamduck = connect(&daemon, Default::default()).await;
+
+    let payload = json!({ "Text": "Hello" });
+    assert_eq!(streamduck.call_raw(name.clone(), payload.clone()).await.unwrap(), payload);
+
+    daemon.respond(name.clone(), MockResponse::Error("Nope".to_string()));
+    assert!(matches!(
+        streamduck.call_raw(name, payload).await,
+        Err(StreamduckError::Daemon { message, .. }) if message == "Nope"
+    ));
+}
+
+#[tokio::test]
+async fn request_data_reaches_daemon() {
+    let daemon = MockDaemon::start().await.unwrap();
+    let streamduck = connect(&daemon, Default::default()).await;
+
+    let identifier = test_device().identifier;
+    streamduck.set_device_autoconnect(identifier.clone(), true).await.unwrap();
+
+    let request = daemon.requests().pop().unwrap();
+    assert_eq!(request.name, NamespacedName::new("Core", "Set Device Autoconnect"));
+    assert_eq!(request.data, json!({
+        "Identifier": identifier,
+        "Autoconnect": true
+    }));
+}
+
+#[tokio::test]
+async fn daemon_error_is_mapped() {
+    let daemon = MockDaemon::start().await.unwrap();
+    let streamduck = connect(&daemon, Default::default()).await;
+
+    let identifier = test_device().identifier;
+    daemon.respond(PopScreen { identifier: identifier.clone() }.name(), MockResponse::Error("Device not found".to_string()));
+
+    match streamduck.pop_screen(identifier).await.unwrap_err() {
+        StreamduckError::Daemon { message, payload } => {
+            assert_eq!(message, "Device not found");
+            assert_eq!(payload, json!({ "Error": "Device not found" }));
+        }
+        error => panic!("Unexpected error: {error}")
+    }
+}
+
+#[tokio::test]
+async fn unexpected_response_keeps_payload() {
+    let daemon = MockDaemon::start().await.unwrap();
+    daemon.respond(ListDevices.name(), MockResponse::data("not a list"));
+
+    let streamduck = connect(&daemon, Default::default()).await;
+
+    match streamduck.list_devices().await.unwrap_err() {
+        StreamduckError::Deserialize { payload, .. } => assert_eq!(payload, json!("not a list")),
+        error => panic!("Unexpected error: {error}")
+    }
+}
+
+#[tokio::test]
+async fn invalid_url_is_reported() {
+    let result = Streamduck::new(Some("not a url")).await;
+    assert!(matches!(result, Err(StreamduckError::InvalidUrl(_))));
+}
+
+#[tokio::test]
+async fn silent_daemon_times_out() {
+    let daemon = MockDaemon::start().await.unwrap();
+    daemon.respond(ListDevices.name(), MockResponse::Silent);
+
+    let streamduck = connect(&daemon, StreamduckConfig {
+        request_timeout: Some(Duration::from_millis(100)),
+        ..Default::default()
+    }).await;
+
+    let error = streamduck.list_devices().await.unwrap_err();
+    assert!(matches!(error, StreamduckError::Timeout(_)));
+
+    // Per-call override
+    daemon.respond(ListDevices.name(), MockResponse::Delayed(Duration::from_millis(200), Box::new(MockResponse::data(json!([])))));
+    assert!(streamduck.with_timeout(Some(Duration::from_secs(5))).list_devices().await.is_ok());
+}
+
+#[tokio::test]
+async fn timed_out_request_is_not_sent_after_reconnect() {
+    let daemon = MockDaemon::start().await.unwrap();
+    let address = daemon.address();
+
+    let streamduck = connect(&daemon, StreamduckConfig {
+        request_timeout: Some(Duration::from_millis(100)),
+        reconnect: ReconnectConfig {
+            initial_delay: Duration::from_millis(50),
+            ..Default::default()
+        },
+        ..Default::default()
+    }).await;
+
+    drop(daemon);
+    wait_for_state(&streamduck, |state| matches!(state, ConnectionState::Reconnecting { .. })).await;
+
+    let identifier = test_device().identifier;
+    assert!(matches!(streamduck.pop_screen(identifier).await, Err(StreamduckError::Timeout(_))));
+
+    let daemon = MockDaemon::start_on(address).await.unwrap();
+    wait_for_state(&streamduck, ConnectionState::is_connected).await;
+    streamduck.list_devices().await.unwrap();
+
+    assert!(daemon.requests().iter().all(|request| request.name != PopScreen { identifier: Default::default() }.name()));
+}
+
+#[tokio::test]
+async fn core_events_are_parsed() {
+    let daemon = MockDaemon::start().await.unwrap();
+    let streamduck = connect(&daemon, Default::default()).await;
+
+    daemon.emit_event("Core", "Device Appeared", Some(json!(test_device())));
+    let event = wait_for(&streamduck, is_from_daemon).await;
+    assert!(matches!(event, StreamduckEvent::DeviceAppeared(device) if device == test_device()));
+
+    daemon.emit_event("Core", "Device Disconnected", Some(json!(test_device().identifier)));
+    let event = wait_for(&streamduck, is_from_daemon).await;
+    assert!(matches!(event, StreamduckEvent::DeviceDisconnected(identifier) if identifier == test_device().identifier));
+
+    daemon.emit_event("Test Plugin", "Something Happened", None);
+    let event = wait_for(&streamduck, is_from_daemon).await;
+    assert!(matches!(event, StreamduckEvent::Other(event) if event.event_name == "Something Happened"));
+
+    daemon.emit_error("Something broke");
+    let event = wait_for(&streamduck, is_from_daemon).await;
+    assert!(matches!(event, StreamduckEvent::SocketError(error) if error == "Something broke"));
+}
+
+#[tokio::test]
+async fn every_subscriber_sees_every_event() {
+    let daemon = MockDaemon::start().await.unwrap();
+    let streamduck = connect(&daemon, Default::default()).await;
+
+    let mut first = streamduck.subscribe();
+    let mut second = streamduck.subscribe();
+
+    daemon.emit_event("Core", "Device Appeared", Some(json!(test_device())));
+
+    for subscriber in [&mut first, &mut second] {
+        let event = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let event = subscriber.recv().await.expect("Client was closed");
+                if is_from_daemon(&event) {
+                    return event;
+                }
+            }
+        }).await.unwrap();
+        assert!(matches!(event, StreamduckEvent::DeviceAppeared(_)));
+    }
+}
+
+#[tokio::test]
+async fn disconnect_fails_pending_requests() {
+    let daemon = MockDaemon::start().await.unwrap();
+    daemon.respond(ListDevices.name(), MockResponse::Silent);
+
+    let streamduck = connect(&daemon, Default::default()).await;
+
+    let request = tokio::spawn({
+        let streamduck = streamduck.clone();
+        async move { streamduck.list_devices().await }
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    daemon.disconnect_clients();
+
+    let error = request.await.unwrap().unwrap_err();
+    assert!(matches!(error, StreamduckError::ConnectionLost));
+}
+
+#[tokio::test]
+async fn idempotent_requests_are_replayed() {
+    let daemon = MockDaemon::start().await.unwrap();
+    daemon.respond(ListDevices.name(), MockResponse::Silent);
+
+    let streamduck = connect(&daemon, StreamduckConfig {
+        replay_policy: ReplayPolicy::ReplayIdempotent,
+        ..Default::default()
+    }).await;
+
+    let request = tokio::spawn({
+        let streamduck = streamduck.clone();
+        async move { streamduck.list_devices().await }
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    daemon.respond(ListDevices.name(), MockResponse::data(vec![test_device()]));
+    daemon.disconnect_clients();
+
+    assert_eq!(request.await.unwrap().unwrap(), vec![test_device()]);
+}
+
+#[tokio::test]
+async fn socket_version_is_checked_on_connect() {
+    let daemon = MockDaemon::start().await.unwrap();
+    let streamduck = connect(&daemon, Default::default()).await;
+
+    let event = wait_for(&streamduck, |event| matches!(event, StreamduckEvent::VersionChecked(_))).await;
+    let StreamduckEvent::VersionChecked(info) = event else { unreachable!() };
+    assert_eq!(info.compatibility, Compatibility::Compatible);
+    assert_eq!(streamduck.connection_info(), Some(info));
+
+    daemon.respond(CoreVersion.name(), MockResponse::data("2.0"));
+    daemon.disconnect_clients();
+
+    let event = wait_for(&streamduck, |event| matches!(event, StreamduckEvent::VersionChecked(_))).await;
+    let StreamduckEvent::VersionChecked(info) = event else { unreachable!() };
+    assert!(info.is_incompatible());
+    assert_eq!(info.socket_version.as_deref(), Some("2.0"));
+}
+
+async fn wait_for_state(streamduck: &Streamduck, predicate: impl Fn(&ConnectionState) -> bool) -> ConnectionState {
+    let mut state = streamduck.watch_connection_state();
+
+    let state = tokio::time::timeout(Duration::from_secs(5), state.wait_for(predicate))
+        .await.expect("State didn't change in time")
+        .expect("State sender was dropped")
+        .clone();
+    state
+}
+
+#[tokio::test]
+async fn connection_state_follows_connection() {
+    let daemon = MockDaemon::start().await.unwrap();
+    let streamduck = connect(&daemon, StreamduckConfig {
+        reconnect: ReconnectConfig {
+            initial_delay: Duration::from_millis(50),
+            ..Default::default()
+        },
+        ..Default::default()
+    }).await;
+
+    assert_eq!(streamduck.connection_state(), ConnectionState::Connected);
+
+    daemon.disconnect_clients();
+    wait_for_state(&streamduck, |state| matches!(state, ConnectionState::Reconnecting { .. })).await;
+    wait_for_state(&streamduck, ConnectionState::is_connected).await;
+
+    streamduck.close();
+    assert_eq!(wait_for_state(&streamduck, ConnectionState::is_closed).await, ConnectionState::Closed { reason: None });
+    assert!(matches!(streamduck.list_devices().await, Err(StreamduckError::ClientClosed)));
+}
+
+#[tokio::test]
+async fn reconnect_gives_up_after_max_attempts() {
+    let daemon = MockDaemon::start().await.unwrap();
+    let url = daemon.url();
+    drop(daemon);
+
+    let streamduck = Streamduck::with_config(StreamduckConfig {
+        url,
+        reconnect: ReconnectConfig {
+            initial_delay: Duration::from_millis(10),
+            max_attempts: Some(3),
+            ..Default::default()
+        },
+        ..Default::default()
+    }).await.unwrap();
+
+    let state = wait_for_state(&streamduck, ConnectionState::is_closed).await;
+    assert!(matches!(state, ConnectionState::Closed { reason: Some(_) }));
+}
+
+#[tokio::test]
+async fn subscriptions_end_after_close() {
+    let daemon = MockDaemon::start().await.unwrap();
+    let streamduck = connect(&daemon, Default::default()).await;
+
+    let subscriber = streamduck.subscribe();
+    streamduck.close();
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        let events: Vec<_> = subscriber.into_stream().collect().await;
+        assert!(events.len() <= 1, "Only disconnect event can arrive after close");
+
+        while streamduck.wait_for_event().await.is_some() {}
+    }).await.expect("Subscription didn't end after close");
+}
+
+#[test]
+fn reconnect_delay_backs_off() {
+    let config = ReconnectConfig {
+        initial_delay: Duration::from_millis(100),
+        max_delay: Duration::from_millis(300),
+        multiplier: 2.0,
+        max_attempts: None
+    };
+
+    assert_eq!(config.delay(1), Duration::from_millis(100));
+    assert_eq!(config.delay(2), Duration::from_millis(200));
+    assert_eq!(config.delay(3), Duration::from_millis(300));
+    assert_eq!(config.delay(50), Duration::from_millis(300));
+}
+
+#[test]
+fn device_events_are_applied() {
+    let mut devices = vec![];
+
+    assert_eq!(apply_event(&mut devices, StreamduckEvent::DeviceAppeared(test_device())), Some(DeviceChange::Added(test_device())));
+    assert_eq!(apply_event(&mut devices, StreamduckEvent::DeviceAppeared(test_device())), None);
+
+    let connected = Device { connected: true, ..test_device() };
+    assert_eq!(apply_event(&mut devices, StreamduckEvent::DeviceConnected(test_device())), Some(DeviceChange::Updated(connected.clone())));
+    assert_eq!(devices, vec![connected]);
+
+    assert_eq!(apply_event(&mut devices, StreamduckEvent::DeviceDisconnected(test_device().identifier)), Some(DeviceChange::Updated(test_device())));
+    assert_eq!(apply_event(&mut devices, StreamduckEvent::DeviceDisconnected(test_device().identifier)), None);
+
+    assert_eq!(apply_event(&mut devices, StreamduckEvent::DeviceDisappeared(test_device().identifier)), Some(DeviceChange::Removed(test_device().identifier)));
+    assert!(devices.is_empty());
+}
+
+async fn wait_for_devices(registry: &DeviceRegistry, predicate: impl Fn(&Vec<Device>) -> bool) {
+    let mut devices = registry.watch();
+
+    tokio::time::timeout(Duration::from_secs(5), devices.wait_for(predicate))
+        .await.expect("Devices didn't change in time")
+        .expect("Registry was dropped");
+}
+
+#[tokio::test]
+async fn device_registry_stays_in_sync() {
+    let daemon = MockDaemon::start().await.unwrap();
+    daemon.respond(ListDevices.name(), MockResponse::data(vec![test_device()]));
+
+    let streamduck = connect(&daemon, StreamduckConfig {
+        reconnect: ReconnectConfig {
+            initial_delay: Duration::from_millis(50),
+            ..Default::default()
+        },
+        ..Default::default()
+    }).await;
+
+    let registry = DeviceRegistry::new(&streamduck);
+    wait_for_devices(&registry, |devices| devices == &vec![test_device()]).await;
+
+    daemon.emit_event("Core", "Device Connected", Some(json!(test_device())));
+    wait_for_devices(&registry, |devices| devices.first().is_some_and(|device| device.connected)).await;
+    assert!(registry.device(&test_device().identifier).unwrap().connected);
+
+    // Device went away while client was disconnected
+    daemon.respond(ListDevices.name(), MockResponse::data(Vec::<Device>::new()));
+    daemon.disconnect_clients();
+    wait_for_devices(&registry, Vec::is_empty).await;
+}
+
+#[tokio::test]
+async fn registry_follows_autoconnect_changes() {
+    let daemon = MockDaemon::start().await.unwrap();
+    daemon.respond(ListDevices.name(), MockResponse::data(vec![test_device()]));
+
+    let streamduck = connect(&daemon, Default::default()).await;
+    let registry = DeviceRegistry::new(&streamduck);
+    wait_for_devices(&registry, |devices| devices == &vec![test_device()]).await;
+
+    let mut changes = registry.subscribe();
+    let identifier = test_device().identifier;
+
+    streamduck.set_device_autoconnect(identifier.clone(), false).await.unwrap();
+    registry.set_autoconnect(&identifier, false);
+
+    let updated = Device { autoconnect: false, ..test_device() };
+    assert_eq!(registry.devices(), vec![updated.clone()]);
+    assert_eq!(changes.try_recv().unwrap(), DeviceChange::Updated(updated));
+
+    // Nothing changes, so nothing is reported
+    registry.set_autoconnect(&identifier, false);
+    assert!(changes.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn device_handle_caches_inputs() {
+    let daemon = MockDaemon::start().await.unwrap();
+    let input = Input { x: 0, y: 0, w: 1, h: 1, icon: InputIcon::Button };
+    daemon.respond(GetDeviceInputs { identifier: test_device().identifier }.name(), MockResponse::data(vec![input.clone()]));
+
+    let streamduck = connect(&daemon, Default::default()).await;
+    let device = streamduck.device(test_device().identifier);
+
+    let count_requests = || daemon.requests().iter()
+        .filter(|request| request.name.name == "Get Device Inputs")
+        .count();
+
+    assert_eq!(device.inputs().await.unwrap(), vec![input.clone()]);
+    assert_eq!(device.clone().inputs().await.unwrap(), vec![input.clone()]);
+    assert_eq!(count_requests(), 1);
+
+    device.refresh_inputs().await.unwrap();
+    assert_eq!(count_requests(), 2);
+
+    let request = daemon.requests().pop().unwrap();
+    assert_eq!(request.data, json!({ "Identifier": test_device().identifier }));
+}
+
+#[tokio::test]
+async fn device_handle_events_are_filtered() {
+    let daemon = MockDaemon::start().await.unwrap();
+    let streamduck = connect(&daemon, Default::default()).await;
+
+    let mut events = streamduck.device(test_device().identifier).events();
+
+    let other_device = Device {
+        identifier: NamespacedDeviceIdentifier {
+            name: NamespacedName::new("Test Plugin", "Test Driver"),
+            identifier: DeviceIdentifier::new("SERIAL02", "Other Device"),
+        },
+        ..test_device()
+    };
+
+    daemon.emit_event("Core", "Device Appeared", Some(json!(other_device)));
+    daemon.emit_event("Test Plugin", "Something Happened", None);
+    daemon.emit_event("Core", "Device Disconnected", Some(json!(test_device().identifier)));
+
+    let event = tokio::time::timeout(Duration::from_secs(5), events.next()).await.unwrap().unwrap();
+    assert!(matches!(event, StreamduckEvent::DeviceDisconnected(identifier) if identifier == test_device().identifier));
+}
+
+#[tokio::test]
+async fn item_properties_round_trip() {
+    let daemon = MockDaemon::start().await.unwrap();
+    let streamduck = connect(&daemon, Default::default()).await;
+    let device = streamduck.device(test_device().identifier);
+
+    assert_eq!(device.item_properties(3).await.unwrap(), None);
+
+    let properties = ItemProperties {
+        renderable: true,
+        action: Some(NamespacedName::new("Test Plugin", "Open Website")),
+        parameters: json!({ "Url": "https://example.com" }).as_object().unwrap().clone(),
+    };
+    device.set_item_properties(3, properties.clone()).await.unwrap();
+
+    let request = daemon.requests().pop().unwrap();
+    assert_eq!(request.name, NamespacedName::new("Core", "Set Item Properties"));
+    assert_eq!(request.data["Input"], json!(3));
+    assert_eq!(request.data["Properties"]["Parameters"]["Url"], json!("https://example.com"));
+
+    daemon.respond(GetItemProperties { identifier: test_device().identifier, input: 3 }.name(), MockResponse::data(&properties));
+    assert_eq!(device.item_properties(3).await.unwrap(), Some(properties));
+}
+
+#[tokio::test]
+async fn retry_skips_reconnect_delay() {
+    let daemon = MockDaemon::start().await.unwrap();
+    let url = daemon.url();
+    drop(daemon);
+
+    let streamduck = Streamduck::with_config(StreamduckConfig {
+        url,
+        reconnect: ReconnectConfig {
+            initial_delay: Duration::from_secs(60),
+            ..Default::default()
+        },
+        ..Default::default()
+    }).await.unwrap();
+
+    wait_for_state(&streamduck, |state| matches!(state, ConnectionState::Reconnecting { attempt: 2, last_error: Some(_) })).await;
+
+    // Retry isn't lost even if the client didn't start waiting yet
+    assert!(streamduck.retry());
+
+    wait_for_state(&streamduck, |state| matches!(state, ConnectionState::Reconnecting { attempt: 3, .. })).await;
+
+    streamduck.close();
+    wait_for_state(&streamduck, ConnectionState::is_closed).await;
+    assert!(!streamduck.retry());
+}
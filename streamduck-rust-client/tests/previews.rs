@@ -0,0 +1,68 @@
+/*
+ * This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at http://mozilla.org/MPL/2.0/.
+ */
+#![cfg(feature = "previews")]
+
+use std::io::Cursor;
+use std::sync::Arc;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use image::{ImageOutputFormat, Rgb, RgbImage};
+use streamduck_rust_client::api::PartialScreenItem;
+use streamduck_rust_client::preview::{PreviewCache, PreviewError};
+
+fn item_with_preview(width: u32, height: u32) -> PartialScreenItem {
+    let image = RgbImage::from_pixel(width, height, Rgb([200, 40, 40]));
+
+    let mut bytes = Cursor::new(vec![]);
+    image.write_to(&mut bytes, ImageOutputFormat::Jpeg(90)).unwrap();
+
+    PartialScreenItem {
+        renderable: true,
+        base64jpg: Some(STANDARD.encode(bytes.into_inner())),
+    }
+}
+
+#[test]
+fn preview_is_decoded() {
+    let preview = item_with_preview(8, 4).decode_preview().unwrap();
+
+    assert_eq!((preview.width, preview.height), (8, 4));
+    assert_eq!(preview.rgba.len(), 8 * 4 * 4);
+    assert_eq!(preview.rgba[3], 255);
+}
+
+#[test]
+fn broken_previews_are_reported() {
+    let cache = PreviewCache::new(4);
+
+    let missing = PartialScreenItem { renderable: true, base64jpg: None };
+    assert!(matches!(cache.decode(&missing), Err(PreviewError::NoPreview)));
+
+    let not_base64 = PartialScreenItem { renderable: true, base64jpg: Some("%%%".to_string()) };
+    assert!(matches!(cache.decode(&not_base64), Err(PreviewError::Base64(_))));
+
+    let not_jpeg = PartialScreenItem { renderable: true, base64jpg: Some(STANDARD.encode("hello")) };
+    assert!(matches!(cache.decode(&not_jpeg), Err(PreviewError::Image(_))));
+
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn decoded_previews_are_cached() {
+    let cache = PreviewCache::new(2);
+    let first = item_with_preview(1, 1);
+
+    let decoded = cache.decode(&first).unwrap();
+    assert!(Arc::ptr_eq(&decoded, &cache.decode(&first.clone()).unwrap()));
+
+    cache.decode(&item_with_preview(2, 2)).unwrap();
+    cache.decode(&first).unwrap();
+    cache.decode(&item_with_preview(3, 3)).unwrap();
+    assert_eq!(cache.len(), 2);
+
+    // Least recently used one got evicted
+    assert!(Arc::ptr_eq(&decoded, &cache.decode(&first).unwrap()));
+}